@@ -1,4 +1,5 @@
 use oxido_sdk::*;
+use std::ptr::{addr_of, addr_of_mut};
 use std::sync::OnceLock;
 use oxido_sdk::{AnimFrame, Animator};
 
@@ -15,7 +16,7 @@ static mut FACE_LEFT: bool = false; // player orientation
 // --- State -----------------------------------------------------------------
 static mut FB: [u8; DEFAULT_W * DEFAULT_H * 4] = [0; DEFAULT_W * DEFAULT_H * 4];
 static mut INPUT_BITS: u32 = 0;
-static mut PREV_INPUT_BITS: u32 = 0;
+static mut INPUT: InputState = InputState::new();
 
 static mut X: f32 = 10.0;         // player
 static mut SCROLL_X: f32 = 0.0;   // map scroll (px)
@@ -119,7 +120,7 @@ static mut AUDIO_STATE: [AudioCh; 4] = [AudioCh{
 
 #[no_mangle]
 pub extern "C" fn oxido_audio_state_ptr() -> *const u8 {
-    unsafe { AUDIO_STATE.as_ptr() as *const u8 }
+    addr_of!(AUDIO_STATE) as *const u8
 }
 #[no_mangle]
 pub extern "C" fn oxido_audio_state_len() -> usize {
@@ -161,6 +162,9 @@ pub extern "C" fn oxido_init() {
 pub extern "C" fn oxido_update(dt_ms: f32) {
     let dt = dt_ms / 1000.0;
     unsafe {
+        let input = &mut *addr_of_mut!(INPUT);
+        input.update(INPUT_BITS);
+
         // scroll Y
         if INPUT_BITS & key_bit(Key::Up)   != 0 { SCROLL_Y -= SCROLL_SPEED * dt; }
         if INPUT_BITS & key_bit(Key::Down) != 0 { SCROLL_Y += SCROLL_SPEED * dt; }
@@ -186,7 +190,7 @@ pub extern "C" fn oxido_update(dt_ms: f32) {
         let moving_v = (INPUT_BITS & (key_bit(Key::Up)   | key_bit(Key::Down)))  != 0;
         let moving = moving_h || moving_v;
 
-        if let Some(ref mut a) = ANIM_PLAYER {
+        if let Some(a) = (*addr_of_mut!(ANIM_PLAYER)).as_mut() {
             a.playing = moving;
             if moving {
                 a.tick(dt_ms);      // advances frames when there is movement on any axis
@@ -204,11 +208,8 @@ pub extern "C" fn oxido_update(dt_ms: f32) {
         SCROLL_X = cam_x as f32;
 
         // Pallettes (edges)
-        let start = key_bit(Key::Start);
-        let select = key_bit(Key::Select);
-        let pressed = |mask: u32| (INPUT_BITS & mask) != 0 && (PREV_INPUT_BITS & mask) == 0;
-        if pressed(start)  { let len=palettes().len(); PAL_IDX = (PAL_IDX + 1) % len; }
-        if pressed(select) { let len=palettes().len(); PAL_IDX = (PAL_IDX + len - 1) % len; }
+        if input.just_pressed(Key::Start)  { let len=palettes().len(); PAL_IDX = (PAL_IDX + 1) % len; }
+        if input.just_pressed(Key::Select) { let len=palettes().len(); PAL_IDX = (PAL_IDX + len - 1) % len; }
 
         // ====== AUDIO DEMO ======
         // Z (A): bip width ADSR + triad arpeggio (0,7,12)
@@ -221,8 +222,6 @@ pub extern "C" fn oxido_update(dt_ms: f32) {
         AUDIO_STATE[2].base_freq = 2200.0;
         AUDIO_STATE[2].vol  = 0.25;
         AUDIO_STATE[2].gate = if b_down { 1 } else { 0 };
-
-        PREV_INPUT_BITS = INPUT_BITS;
     }
 }
 
@@ -230,16 +229,16 @@ pub extern "C" fn oxido_update(dt_ms: f32) {
 #[no_mangle]
 pub extern "C" fn oxido_draw_ptr() -> *const u8 {
     unsafe {
-        let mut f = Frame { data: &mut FB, w: DEFAULT_W, h: DEFAULT_H };
+        let mut f = Frame { data: &mut *addr_of_mut!(FB), w: DEFAULT_W, h: DEFAULT_H };
         let pal = current_pal();
 
         // Background and player
         map().draw(&mut f, atlas(), pal, SCROLL_X as i32, SCROLL_Y as i32, false);
 
         // Player (sprite 8x8 centered in hitbox 16x16)
-        let (fx, fy, tile) = if let Some(ref a) = ANIM_PLAYER {
+        let (fx, fy, tile) = if let Some(a) = (*addr_of!(ANIM_PLAYER)).as_ref() {
             let fr = a.current();
-            (fr.fx ^ unsafe { FACE_LEFT }, fr.fy, fr.tile)
+            (fr.fx ^ FACE_LEFT, fr.fy, fr.tile)
         } else { (false, false, 0) };
 
         let xi = X as i32;
@@ -267,9 +266,10 @@ pub extern "C" fn oxido_draw_ptr() -> *const u8 {
 
         // HUD
         f.rect(1, 1, 158, 14, pal.color(1));
-        f.text5x7(4, 4, &format!("PAL {}  Z=ADSR+ARP  X=NOISE", unsafe { PAL_IDX }), pal.color(3));
+        let pal_idx = PAL_IDX;
+        f.text5x7(4, 4, &format!("PAL {pal_idx}  Z=ADSR+ARP  X=NOISE"), pal.color(3));
 
-        FB.as_ptr()
+        addr_of!(FB) as *const u8
     }
 }
 
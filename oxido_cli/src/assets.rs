@@ -0,0 +1,104 @@
+use anyhow::*;
+use oxido_sdk::{Palette, ATLAS_MAGIC};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// `[assets]` section of manifest.toml: art converted while packing.
+#[derive(Deserialize, Default)]
+pub struct AssetsSection {
+    #[serde(default)]
+    pub atlas: Vec<AtlasSpec>,
+}
+
+/// One `[[assets.atlas]]` entry.
+#[derive(Deserialize)]
+pub struct AtlasSpec {
+    /// PNG path, relative to `<game>/cart`
+    pub png: String,
+    pub tile_w: u16,
+    pub tile_h: u16,
+    /// Palette to quantize to: "gb" (default) or "gray"
+    pub palette: Option<String>,
+}
+
+fn named_palette(name: &str) -> Result<Palette> {
+    match name.to_ascii_lowercase().as_str() {
+        "gb" => Ok(Palette::GB),
+        "gray" | "grey" => Ok(Palette::GRAY),
+        other => bail!("unknown palette '{other}' (expected gb|gray)"),
+    }
+}
+
+/// Converts every atlas listed in the manifest and writes `<png stem>.atlas`
+/// into `out_dir`. Returns the names written.
+pub fn build_atlases(cart_src: &Path, assets: &AssetsSection, out_dir: &Path) -> Result<Vec<String>> {
+    let mut written = Vec::new();
+    for spec in &assets.atlas {
+        let png_path = cart_src.join(&spec.png);
+        let pal = named_palette(spec.palette.as_deref().unwrap_or("gb"))
+            .with_context(|| format!("[[assets.atlas]] {}", spec.png))?;
+        let bytes = convert_png(&png_path, spec.tile_w, spec.tile_h, &pal)
+            .with_context(|| format!("Could not convert {}", png_path.display()))?;
+
+        let stem = png_path.file_stem().and_then(|s| s.to_str()).unwrap_or("atlas");
+        let name = format!("{stem}.atlas");
+        fs::write(out_dir.join(&name), bytes)?;
+        written.push(name);
+    }
+    Ok(written)
+}
+
+/// PNG → `.atlas` bytes (see `SpriteAtlas::from_bytes`). Pixels with alpha
+/// below 128 become index 0; the rest map to the nearest palette color.
+fn convert_png(path: &Path, tile_w: u16, tile_h: u16, pal: &Palette) -> Result<Vec<u8>> {
+    ensure!(tile_w > 0 && tile_h > 0, "tile_w and tile_h must be nonzero");
+
+    let mut decoder = png::Decoder::new(fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    let (w, h) = (info.width as usize, info.height as usize);
+    let channels = info.color_type.samples();
+
+    // Crop to whole tiles: the SDK refuses atlases that tiles don't divide
+    let (tw, th) = (tile_w as usize, tile_h as usize);
+    let (cw, ch) = (w / tw * tw, h / th * th);
+    if (cw, ch) != (w, h) {
+        eprintln!(
+            "⚠️  {}: {w}x{h} is not a multiple of {tw}x{th}; cropping to {cw}x{ch}",
+            path.display()
+        );
+    }
+    ensure!(cw > 0 && ch > 0, "image is smaller than one {tw}x{th} tile");
+    ensure!(cw <= u16::MAX as usize && ch <= u16::MAX as usize, "image is too large");
+
+    let mut out = Vec::with_capacity(12 + cw * ch);
+    out.extend_from_slice(&ATLAS_MAGIC);
+    for v in [cw as u16, ch as u16, tile_w, tile_h] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    for y in 0..ch {
+        let row = &buf[y * info.line_size..];
+        for x in 0..cw {
+            let px = &row[x * channels..(x + 1) * channels];
+            let (rgb, alpha) = match channels {
+                1 => ([px[0]; 3], 255),
+                2 => ([px[0]; 3], px[1]),
+                3 => ([px[0], px[1], px[2]], 255),
+                _ => ([px[0], px[1], px[2]], px[3]),
+            };
+            out.push(if alpha < 128 { 0 } else { nearest_index(pal, rgb) });
+        }
+    }
+    Ok(out)
+}
+
+fn nearest_index(pal: &Palette, rgb: [u8; 3]) -> u8 {
+    let dist = |c: u32| {
+        let [r, g, b, _] = c.to_le_bytes();
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, rgb[0]) + d(g, rgb[1]) + d(b, rgb[2])
+    };
+    (0..4u8).min_by_key(|&i| dist(pal.color(i))).unwrap_or(0)
+}
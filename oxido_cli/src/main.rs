@@ -1,8 +1,14 @@
+mod assets;
+
 use anyhow::*;
-use clap::{Parser, Subcommand};
-use oxido_core::runtime::{run, Cartridge};
+use assets::AssetsSection;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use oxido_core::filter::{Background, Filter, PaletteOverride, ScaleMode};
+use oxido_core::launcher::{run_launcher, LauncherEntry};
+use oxido_core::runtime::{copy_frame_rect, run, Cartridge, Crush, Echo, Headless, LiveManifest, LiveSettings, UnpackedDir, DEFAULT_FUEL, DEFAULT_MAX_DT_MS, LOWPASS_RANGE, SPEED_RANGE};
+use flate2::{write::GzEncoder, Compression};
 use serde::Deserialize;
-use std::{fs, path::{Path, PathBuf}, process::Command};
+use std::{ffi::OsString, fs, io::Write, path::{Path, PathBuf}, process::Command, sync::mpsc, thread, time::Duration};
 
 #[derive(Parser)]
 #[command(name = "oxido")]
@@ -19,21 +25,18 @@ enum Cmd {
         /// Route to .wasm or .cart folder
         #[arg(value_name = "PATH")]
         path: String,
-        /// Width of framebuffer (used only if PATH is .wasm)
-        #[arg(long, default_value_t = 160)]
-        width: u32,
-        /// Height of framebuffer (used only if PATH is .wasm)
-        #[arg(long, default_value_t = 144)]
-        height: u32,
-        /// Window scale factor (pixel-perfect)
-        #[arg(short, long, default_value_t = 3)]
-        scale: u32,
+        #[command(flatten)]
+        opts: RunOpts,
     },
     /// Creates a new game (template) in a folder
     New {
         /// Game name and destination folder
         #[arg(value_name = "NAME")]
         name: String,
+        /// Starting point: minimal (moving square), tilemap (map + atlas + camera)
+        /// or audio (ADSR demo)
+        #[arg(long, value_enum, default_value = "minimal")]
+        template: Template,
     },
     /// Package a game into a .cart folder (builds WASM and copies manifest/assets)
     Pack {
@@ -43,59 +46,839 @@ enum Cmd {
         /// Output folder (.cart). Default: <GAME_DIR>/build/cart
         #[arg(long)]
         out: Option<String>,
+        /// Skip compilation and package the last `oxido build` artifact
+        #[arg(long)]
+        no_build: bool,
+        /// Also write a single-file zip cart (default: <GAME_DIR>/build/game.cart,
+        /// or the --out path); the folder is staged in <GAME_DIR>/build/cart
+        #[arg(long)]
+        zip: bool,
+        /// Shrink game.wasm with `wasm-opt -Oz` (Binaryen); skipped with a warning if it isn't on PATH
+        #[arg(long)]
+        optimize: bool,
+    },
+    /// Compile a game to wasm32-unknown-unknown (release) without packaging it
+    Build {
+        /// Root folder of the game (where its Cargo.toml is)
+        #[arg(value_name = "GAME_DIR", default_value = ".")]
+        game_dir: String,
+    },
+    /// Print a cart's manifest metadata and bundled files without running it
+    Info {
+        /// .cart folder or .cart zip
+        #[arg(value_name = "PATH")]
+        path: String,
+    },
+    /// Run a game headless as fast as possible and report update/draw timings
+    Bench {
+        /// Route to .wasm or .cart folder
+        #[arg(value_name = "PATH")]
+        path: String,
+        /// Number of update+draw cycles to time
+        #[arg(long, default_value_t = 600)]
+        frames: u32,
+        /// Width of framebuffer (used only if PATH is .wasm)
+        #[arg(long, default_value_t = 160)]
+        width: u32,
+        /// Height of framebuffer (used only if PATH is .wasm)
+        #[arg(long, default_value_t = 144)]
+        height: u32,
+        /// Per-call fuel budget, as in `run` (0 = unlimited, slightly faster)
+        #[arg(long, default_value_t = DEFAULT_FUEL)]
+        fuel: u64,
+    },
+    /// Write a static HTML+JS page that plays a cart in the browser (e.g. for itch.io)
+    ExportWeb {
+        /// .cart folder or .cart zip
+        #[arg(value_name = "CART")]
+        cart: String,
+        /// Output folder. Default: a `web` folder next to CART
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Pack and run a game, rebuilding it whenever src/ changes (hot-reloads the running cart)
+    Watch {
+        /// Root folder of the game (where its Cargo.toml is)
+        #[arg(value_name = "GAME_DIR", default_value = ".")]
+        game_dir: String,
+        /// Output folder (.cart). Default: <GAME_DIR>/build/cart
+        #[arg(long)]
+        out: Option<String>,
+        #[command(flatten)]
+        opts: RunOpts,
     },
 }
 
+/// Built-in game templates for `oxido new`.
+#[derive(Clone, Copy, ValueEnum)]
+enum Template {
+    Minimal,
+    Tilemap,
+    Audio,
+}
+
+/// Runtime options shared by `run` and `watch`.
+#[derive(Args)]
+struct RunOpts {
+    /// Width of framebuffer (used only if PATH is .wasm)
+    #[arg(long, default_value_t = 160)]
+    width: u32,
+    /// Height of framebuffer (used only if PATH is .wasm)
+    #[arg(long, default_value_t = 144)]
+    height: u32,
+    /// Window scale factor (pixel-perfect)
+    #[arg(short, long, default_value_t = 3)]
+    scale: u32,
+    /// Per-call fuel budget for oxido_update/oxido_draw_ptr (0 = unlimited)
+    #[arg(long, default_value_t = DEFAULT_FUEL)]
+    fuel: u64,
+    /// Post-process filter: none | scanlines | crt | dmg (overrides the manifest)
+    #[arg(long)]
+    filter: Option<Filter>,
+    /// How the image fits the window: integer (crisp, with bars) | stretch
+    #[arg(long, default_value = "integer")]
+    scale_mode: ScaleMode,
+    /// Color of the bars around the image as #RRGGBB (overrides the manifest; default black)
+    #[arg(long, value_name = "HEX")]
+    bg: Option<Background>,
+    /// Audio output device by name (the list is printed if it isn't found)
+    #[arg(long)]
+    audio_device: Option<String>,
+    /// Audio sample rate in Hz (default: the device's)
+    #[arg(long)]
+    sample_rate: Option<u32>,
+    /// Also write the audio output to this WAV file (stereo 32-bit float)
+    #[arg(long, value_name = "FILE")]
+    record_audio: Option<PathBuf>,
+    /// Time oxido_update/oxido_draw_ptr and print a summary table on exit
+    #[arg(long)]
+    profile: bool,
+    /// Keep playing audio while the window is unfocused (muted by default)
+    #[arg(long)]
+    background_audio: bool,
+    /// Window without title bar or borders (also `borderless = true` in the manifest)
+    #[arg(long)]
+    borderless: bool,
+    /// Keep the window above others (also `on_top = true` in the manifest)
+    #[arg(long)]
+    on_top: bool,
+    /// Force an accessible palette: high-contrast | deuteranopia | tritanopia (F4 cycles)
+    #[arg(long)]
+    palette: Option<PaletteOverride>,
+    /// Game time multiplier: 0.5 = slow motion, 2 = fast forward (0.05..=8)
+    #[arg(long, default_value_t = 1.0)]
+    speed: f32,
+    /// Cap on the frame time passed to oxido_update, in ms (0 = no cap)
+    #[arg(long, value_name = "MS", default_value_t = DEFAULT_MAX_DT_MS)]
+    max_dt: f32,
+    /// Deterministic updates: call oxido_update with this dt (ms) as often as elapsed time requires
+    #[arg(long, value_name = "MS")]
+    fixed_dt: Option<f32>,
+    /// Echo over the whole mix as MS,FEEDBACK,MIX, e.g. 250,0.4,0.3 (overrides the manifest)
+    #[arg(long, value_name = "MS,FEEDBACK,MIX")]
+    echo: Option<Echo>,
+    /// Soften the output with a low-pass at this cutoff in Hz, 20..=20000 (overrides the manifest)
+    #[arg(long, value_name = "HZ")]
+    lowpass: Option<f32>,
+    /// Keep this many seconds of play to step back through by holding Backspace (0 = off, max 120)
+    #[arg(long, value_name = "SECONDS", default_value_t = 0)]
+    rewind_seconds: u32,
+}
+
 #[derive(Deserialize)]
 struct Manifest {
     title: Option<String>,
     version: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+    /// PNG (relative to the cart) used as the window icon
+    icon: Option<String>,
     width: Option<u32>,
     height: Option<u32>,
     /// binary name of the wasm inside the .cart (default "game.wasm")
     wasm: Option<String>,
     /// Optional window scale (pixel-perfect)
-    scale: Option<u32>,                  
+    scale: Option<u32>,
+    /// Optional post-process filter: "none", "scanlines", "crt" or "dmg"
+    filter: Option<String>,
+    /// Letterbox color, "#RRGGBB"
+    bg: Option<String>,
+    /// Window without title bar or borders
+    #[serde(default)]
+    borderless: bool,
+    /// Keep the window above others
+    #[serde(default)]
+    on_top: bool,
+    /// PNGs converted to indexed atlases by `oxido pack`
+    #[serde(default)]
+    assets: AssetsSection,
+    /// WAV files (relative to the cart) for `oxido_play_sfx`; the index is the id
+    #[serde(default)]
+    sfx: Vec<String>,
+    /// Global audio effects
+    #[serde(default)]
+    audio: AudioSection,
+}
+
+/// `[audio]` in manifest.toml.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AudioSection {
+    /// `echo = { time_ms = 250, feedback = 0.4, mix = 0.3 }`
+    echo: Option<EchoSpec>,
+    /// Low-pass cutoff in Hz
+    lowpass: Option<f32>,
+    /// `crush = { bits = 4, rate_div = 4 }`
+    crush: Option<CrushSpec>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EchoSpec {
+    time_ms: f32,
+    feedback: f32,
+    mix: f32,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CrushSpec {
+    #[serde(default = "full_bits")]
+    bits: u32,
+    #[serde(default = "no_rate_div")]
+    rate_div: u32,
+}
+
+fn full_bits() -> u32 { 16 }
+fn no_rate_div() -> u32 { 1 }
+
+impl From<CrushSpec> for Crush {
+    fn from(c: CrushSpec) -> Self {
+        Crush { bits: c.bits, rate_div: c.rate_div }
+    }
+}
+
+impl From<EchoSpec> for Echo {
+    fn from(e: EchoSpec) -> Self {
+        Echo { time_ms: e.time_ms, feedback: e.feedback, mix: e.mix }
+    }
+}
+
+/// Window title when the cart doesn't name itself.
+const DEFAULT_TITLE: &str = "OxidoBoy";
+
+/// Keys `Manifest` understands; anything else is reported as unknown.
+const MANIFEST_KEYS: &[&str] = &["title", "version", "author", "description", "icon", "width", "height", "wasm", "scale", "filter", "bg", "borderless", "on_top", "assets", "sfx", "audio"];
+
+/// Top-level manifest keys that no part of OxidoBoy reads (typos, stale fields).
+fn unknown_manifest_keys(src: &str) -> Vec<String> {
+    toml::from_str::<toml::Table>(src)
+        .map(|t| t.keys().filter(|k| !MANIFEST_KEYS.contains(&k.as_str())).cloned().collect())
+        .unwrap_or_default()
+}
+
+impl Manifest {
+    /// Parses manifest.toml; syntax and type errors name the key and line.
+    fn parse(src: &str) -> Result<Self> {
+        toml::from_str(src).map_err(|e| anyhow!("manifest.toml: {}", e.to_string().trim_end()))
+    }
+
+    /// Values that parse but can't run. `has_file` tells whether a
+    /// cart-relative path exists.
+    fn problems(&self, has_file: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut out = Vec::new();
+        for (field, v) in [("width", self.width), ("height", self.height)] {
+            if v == Some(0) { out.push(format!("`{field}` must be nonzero")); }
+        }
+        if self.scale == Some(0) {
+            out.push("`scale` must be >= 1".to_string());
+        }
+        if let Some(Err(e)) = self.filter.as_deref().map(str::parse::<Filter>) {
+            out.push(format!("`filter`: {e}"));
+        }
+        if let Some(Err(e)) = self.bg.as_deref().map(str::parse::<Background>) {
+            out.push(format!("`bg`: {e}"));
+        }
+        if let Some(Err(e)) = self.audio.echo.map(|e| Echo::from(e).check()) {
+            out.push(format!("`audio.echo`: {e}"));
+        }
+        if let Some(Err(e)) = self.audio.crush.map(|c| Crush::from(c).check()) {
+            out.push(format!("`audio.crush`: {e}"));
+        }
+        if let Some(hz) = self.audio.lowpass.filter(|hz| !LOWPASS_RANGE.contains(hz)) {
+            out.push(format!("`audio.lowpass` must be between {} and {} Hz (got {hz})", LOWPASS_RANGE.start(), LOWPASS_RANGE.end()));
+        }
+        for f in &self.sfx {
+            if !has_file(f) { out.push(format!("`sfx` entry \"{f}\" is not in the cart")); }
+        }
+        let wasm = self.wasm_name();
+        if !has_file(&wasm) {
+            out.push(format!("`wasm` = \"{wasm}\" is not in the cart"));
+        }
+        out
+    }
+
+    fn wasm_name(&self) -> String {
+        self.wasm.clone().unwrap_or_else(|| "game.wasm".to_string())
+    }
+}
+
+/// Parses and validates a cart's manifest: unknown keys warn, invalid values fail.
+fn load_manifest(src: &str, has_file: impl Fn(&str) -> bool) -> Result<Manifest> {
+    let man = Manifest::parse(src)?;
+    for key in unknown_manifest_keys(src) {
+        eprintln!("⚠️  manifest.toml: unknown field `{key}` (ignored)");
+    }
+    let problems = man.problems(has_file);
+    ensure!(problems.is_empty(), "manifest.toml:\n  - {}", problems.join("\n  - "));
+    Ok(man)
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.cmd {
-        Cmd::Run { path, width, height,scale } => cmd_run(path, width, height,scale),
-        Cmd::New { name } => cmd_new(name),
-        Cmd::Pack { game_dir, out } => cmd_pack(game_dir, out),
+        Cmd::Run { path, opts } => cmd_run(path, opts),
+        Cmd::New { name, template } => cmd_new(name, template),
+        Cmd::Pack { game_dir, out, no_build, zip, optimize } => cmd_pack(game_dir, out, no_build, zip, optimize),
+        Cmd::Build { game_dir } => cmd_build(game_dir),
+        Cmd::Watch { game_dir, out, opts } => cmd_watch(game_dir, out, opts),
+        Cmd::Info { path } => cmd_info(path),
+        Cmd::Bench { path, frames, width, height, fuel } => cmd_bench(path, frames, width, height, fuel),
+        Cmd::ExportWeb { cart, out } => cmd_export_web(cart, out),
     }
 }
 
-fn cmd_run(path: String, width: u32, height: u32, scale: u32) -> Result<()> {
+fn cmd_run(path: String, opts: RunOpts) -> Result<()> {
     let p = Path::new(&path);
+    if p.is_dir() && !p.join("manifest.toml").exists() {
+        return cmd_launcher(p);
+    }
+    run(load_cartridge(&path, opts)?)
+}
+
+/// `oxido run <DIR>` on a folder of carts: pick one from a menu, play it in
+/// a child `oxido run` (with the same options), come back when it exits.
+fn cmd_launcher(dir: &Path) -> Result<()> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Could not be read {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("cart"))
+        .collect();
+    paths.sort();
+
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for path in paths {
+        let checked = read_cart_listing(&path).and_then(|(src, files)| {
+            let man = Manifest::parse(&src)?;
+            let problems = man.problems(|f| files.iter().any(|(n, _)| n == f));
+            ensure!(problems.is_empty(), "{}", problems.join("; "));
+            Ok(man.title)
+        });
+        match checked {
+            std::result::Result::Ok(title) => {
+                let title = title.unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+                entries.push(LauncherEntry { title, path });
+            }
+            Err(e) => {
+                eprintln!("⚠️  skipping {}: {e:#}", path.display());
+                skipped += 1;
+            }
+        }
+    }
+    ensure!(!entries.is_empty(), "{} has no manifest.toml and no playable .cart inside", dir.display());
+    let notes = match skipped {
+        0 => Vec::new(),
+        1 => vec!["1 cart skipped (see the terminal)".to_string()],
+        n => vec![format!("{n} carts skipped (see the terminal)")],
+    };
+
+    // re-run ourselves with the same arguments, the folder swapped for the cart
+    let exe = std::env::current_exe().context("could not locate the oxido executable")?;
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    run_launcher(entries, notes, move |entry| {
+        let args = relaunch_args(&argv, &entry.path)?;
+        Command::new(&exe).args(args).spawn().context("could not start the game")
+    })
+}
+
+/// `argv` of an `oxido run <DIR> ...` with only the positional PATH swapped
+/// for `cart` (an option value that happens to equal it stays), minus the
+/// executable name.
+fn relaunch_args(argv: &[OsString], cart: &Path) -> Result<Vec<OsString>> {
+    let path_of = |argv: &[OsString]| match Cli::try_parse_from(argv) {
+        std::result::Result::Ok(Cli { cmd: Cmd::Run { path, .. } }) => Some(path),
+        _ => None,
+    };
+    let dir = path_of(argv).context("not an `oxido run` command line")?;
+    // PATH is whichever copy of it clap reads back as PATH when marked
+    const MARK: &str = "\0oxido-cart";
+    let mut args = argv.to_vec();
+    let idx = (1..args.len())
+        .filter(|&i| args[i] == *dir)
+        .find(|&i| {
+            let mut marked = argv.to_vec();
+            marked[i] = MARK.into();
+            path_of(&marked).as_deref() == Some(MARK)
+        })
+        .context("could not find the cart path in the command line")?;
+    args[idx] = cart.as_os_str().to_owned();
+    args.remove(0);
+    Ok(args)
+}
+
+/// Resolves PATH (.wasm, .cart folder or .cart zip) plus the command-line
+/// options into what the runtime needs.
+fn load_cartridge(path: &str, opts: RunOpts) -> Result<Cartridge> {
+    let p = Path::new(path);
+    ensure!(
+        SPEED_RANGE.contains(&opts.speed),
+        "--speed must be between {} and {} (got {})", SPEED_RANGE.start(), SPEED_RANGE.end(), opts.speed
+    );
+    ensure!(opts.max_dt >= 0.0, "--max-dt must be 0 (no cap) or a positive number of ms");
+    if let Some(dt) = opts.fixed_dt {
+        ensure!((1.0..=1000.0).contains(&dt), "--fixed-dt must be between 1 and 1000 ms (got {dt})");
+    }
+    ensure!(opts.rewind_seconds <= 120, "--rewind-seconds must be at most 120 (got {})", opts.rewind_seconds);
+    if let Some(hz) = opts.lowpass {
+        ensure!(LOWPASS_RANGE.contains(&hz), "--lowpass must be between {} and {} Hz (got {hz})", LOWPASS_RANGE.start(), LOWPASS_RANGE.end());
+    }
 
     if p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("wasm") {
         // Run directly a wasm file
-        return run(Cartridge { wasm_path: p.to_path_buf(), w: width, h: height,scale });
+        let RunOpts { width, height, scale, fuel, filter, scale_mode, bg, audio_device, sample_rate, record_audio, profile, background_audio, borderless, on_top, palette, speed, max_dt, fixed_dt, echo, lowpass, rewind_seconds } = opts;
+        let save_path = Some(p.with_extension("sav"));
+        let filter = filter.unwrap_or_default();
+        return Ok(Cartridge {
+            title: DEFAULT_TITLE.to_string(), wasm_path: p.to_path_buf(), w: width, h: height, scale, save_path, fuel, filter, scale_mode, bg: bg.unwrap_or_default(), icon: None,
+            audio_device, sample_rate, assets_dir: None, sfx: Vec::new(), record_audio, profile, background_audio, borderless, on_top, palette, speed, max_dt_ms: max_dt, fixed_dt_ms: fixed_dt, echo, lowpass_hz: lowpass, crush: None, manifest: None, rewind_seconds, unpacked: None,
+        });
+    }
+
+    if p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("cart") {
+        // Zipped cart: unpack to a temp folder, keep saves next to the archive
+        let dir = extract_cart_zip(p)?;
+        let mut cart = cart_from_dir(dir.path(), p, opts)?;
+        cart.unpacked = Some(dir);
+        return Ok(cart);
+    }
+
+    if p.is_dir() {
+        return cart_from_dir(p, p, opts);
+    }
+
+    bail!("PATH must be a .wasm, a .cart folder or a .cart zip");
+}
+
+/// Reads an unpacked cart folder. `cart_loc` is where the cart lives for the
+/// player (the folder itself, or the .cart zip) and decides where saves go.
+fn cart_from_dir(p: &Path, cart_loc: &Path, opts: RunOpts) -> Result<Cartridge> {
+    let RunOpts { width, height, scale, fuel, filter, scale_mode, bg, audio_device, sample_rate, record_audio, profile, background_audio, borderless, on_top, palette, speed, max_dt, fixed_dt, echo, lowpass, rewind_seconds } = opts;
+
+    // Upload .cart folder manifest
+    let manifest_path = p.join("manifest.toml");
+    let s = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Could not be read {}", manifest_path.display()))?;
+    let man = load_manifest(&s, |f| p.join(f).is_file())?;
+
+    // what the manifest decides over the command line, now and on live reloads
+    let settings = move |man: &Manifest| LiveSettings {
+        title: man.title.clone().unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+        w: man.width.unwrap_or(width),
+        h: man.height.unwrap_or(height),
+        scale: man.scale.unwrap_or(scale),
+        filter: match (filter, man.filter.as_deref()) {
+            (Some(f), _) => f,
+            (None, name) => name.and_then(|n| n.parse().ok()).unwrap_or_default(),
+        },
+        bg: bg.or_else(|| man.bg.as_deref().and_then(|c| c.parse().ok())).unwrap_or_default(),
+    };
+    let LiveSettings { title, w, h, scale: s, filter, bg } = settings(&man);
+    let wasm_path = p.join(man.wasm_name());
+    let save_path = Some(save_path_for(cart_loc, man.title.as_deref()));
+    // a zipped cart is unpacked to a temp folder nobody edits
+    let dir = p.to_path_buf();
+    let manifest = (p == cart_loc).then(|| LiveManifest {
+        path: manifest_path.clone(),
+        parse: Box::new(move |src: &str| Ok(settings(&load_manifest(src, |f| dir.join(f).is_file())?))),
+    });
+
+    let icon = man.icon.map(|i| p.join(i));
+    let sfx = man.sfx.iter().map(|f| p.join(f)).collect();
+    let assets_dir = Some(p.join("assets"));
+    let echo = echo.or(man.audio.echo.map(Echo::from));
+    let lowpass = lowpass.or(man.audio.lowpass);
+    let crush = man.audio.crush.map(Crush::from);
+    let (borderless, on_top) = (borderless || man.borderless, on_top || man.on_top);
+
+    Ok(Cartridge { title, wasm_path, w, h, scale: s, save_path, fuel, filter, scale_mode, bg, icon, audio_device, sample_rate, assets_dir, sfx, record_audio, profile, background_audio, borderless, on_top, palette, speed, max_dt_ms: max_dt, fixed_dt_ms: fixed_dt, echo, lowpass_hz: lowpass, crush, manifest, rewind_seconds, unpacked: None })
+}
+
+fn cmd_bench(path: String, frames: u32, width: u32, height: u32, fuel: u64) -> Result<()> {
+    ensure!(frames > 0, "--frames must be at least 1");
+    let opts = RunOpts {
+        width, height, scale: 1, fuel, filter: None, scale_mode: ScaleMode::Integer, bg: None,
+        audio_device: None, sample_rate: None, record_audio: None, profile: false, background_audio: false, borderless: false, on_top: false, palette: None, speed: 1.0, max_dt: DEFAULT_MAX_DT_MS, fixed_dt: None, echo: None, lowpass: None, rewind_seconds: 0,
+    };
+    let mut cart = load_cartridge(&path, opts)?;
+    cart.save_path = None; // a benchmark must not touch the player's save
+
+    let mut game = Headless::new(&cart)?;
+    let (mut update_ms, mut draw_ms) = (Vec::new(), Vec::new());
+    // host-side copy out of guest memory, as the windowed runtime does it:
+    // only the `oxido_dirty_rect` area vs. the whole frame (carts that export it)
+    let (mut host_fb, mut dirty_copy_ms, mut full_copy_ms) = (Vec::new(), Vec::new(), Vec::new());
+    let t0 = std::time::Instant::now();
+    for i in 0..frames {
+        let t = std::time::Instant::now();
+        game.update(0, 1000.0 / 60.0).with_context(|| format!("oxido_update, frame {i}"))?;
+        update_ms.push(t.elapsed().as_secs_f64() * 1000.0);
+        let (w, h) = game.size();
+        let t = std::time::Instant::now();
+        let (src, rect) = game.draw_dirty().with_context(|| format!("oxido_draw_ptr, frame {i}"))?;
+        draw_ms.push(t.elapsed().as_secs_f64() * 1000.0);
+        if let Some(rect) = rect {
+            host_fb.resize(src.len(), 0);
+            let t = std::time::Instant::now();
+            copy_frame_rect(src, &mut host_fb, w, rect, false);
+            dirty_copy_ms.push(t.elapsed().as_secs_f64() * 1000.0);
+            let t = std::time::Instant::now();
+            copy_frame_rect(src, &mut host_fb, w, (0, 0, w as usize, h as usize), false);
+            full_copy_ms.push(t.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+    let total_s = t0.elapsed().as_secs_f64();
+    let frame_ms: Vec<f64> = update_ms.iter().zip(&draw_ms).map(|(u, d)| u + d).collect();
+
+    println!("⏱️  {} — {frames} frames", cart.wasm_path.display());
+    println!("          avg      p50      p95      p99      max   (ms)");
+    for (name, samples) in [("update", &update_ms), ("draw", &draw_ms), ("frame", &frame_ms)] {
+        let mut v = samples.clone();
+        v.sort_by(f64::total_cmp);
+        let pct = |p: f64| v[((v.len() - 1) as f64 * p).round() as usize];
+        let avg = v.iter().sum::<f64>() / v.len() as f64;
+        println!("{name:<7} {avg:8.3} {:8.3} {:8.3} {:8.3} {:8.3}", pct(0.5), pct(0.95), pct(0.99), v[v.len() - 1]);
+    }
+    println!("effective FPS: {:.0} (budget at 60 Hz: 16.67 ms/frame)", frames as f64 / total_s);
+    if !dirty_copy_ms.is_empty() {
+        let avg = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+        println!(
+            "host copy: {:.4} ms with oxido_dirty_rect vs {:.4} ms for the full frame (avg; the GPU upload is always full)",
+            avg(&dirty_copy_ms), avg(&full_copy_ms)
+        );
+    }
+
+    // frame time histogram
+    const BUCKETS: [(f64, &str); 6] = [
+        (1.0, "   < 1 ms"), (2.0, "  1-2 ms"), (4.0, "  2-4 ms"), (8.0, "  4-8 ms"), (16.67, " 8-16.7 ms"), (f64::INFINITY, " > 16.7 ms"),
+    ];
+    let mut counts = [0usize; BUCKETS.len()];
+    for &ms in &frame_ms {
+        counts[BUCKETS.iter().position(|&(hi, _)| ms < hi).unwrap_or(BUCKETS.len() - 1)] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(1).max(1);
+    for ((_, label), n) in BUCKETS.iter().zip(counts) {
+        println!("{label:>10} | {:<40} {n}", "#".repeat((n * 40).div_ceil(max)));
+    }
+    let over = counts[BUCKETS.len() - 1];
+    if over > 0 {
+        println!("⚠️  {over} frame(s) over the 16.7 ms budget");
+    }
+    Ok(())
+}
+
+/// Most a zipped .cart may unpack to, so a zip bomb can't fill the disk.
+const CART_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Unpacks a zipped .cart into a per-process temp folder, deleted when the
+/// returned guard drops.
+fn extract_cart_zip(zip_path: &Path) -> Result<UnpackedDir> {
+    extract_cart_zip_capped(zip_path, CART_MAX_BYTES)
+}
+
+fn extract_cart_zip_capped(zip_path: &Path, max_bytes: u64) -> Result<UnpackedDir> {
+    let file = fs::File::open(zip_path)
+        .with_context(|| format!("Could not open {}", zip_path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("{} is not a valid .cart zip", zip_path.display()))?;
+
+    let dir = std::env::temp_dir().join(format!("oxido-cart-{}", std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    // from here on the folder goes away on any error
+    let unpacked = UnpackedDir(dir.clone());
+
+    // counted on the bytes actually written, not the sizes the zip claims
+    let mut budget = max_bytes;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        // skip entries that would escape the folder (../, absolute paths)
+        let Some(rel) = entry.enclosed_name().map(Path::to_path_buf) else { continue };
+        let dst = dir.join(rel);
+        if entry.is_dir() {
+            fs::create_dir_all(&dst)?;
+        } else {
+            if let Some(parent) = dst.parent() { fs::create_dir_all(parent)?; }
+            let mut out = fs::File::create(&dst)?;
+            let n = std::io::copy(&mut std::io::Read::take(&mut entry, budget + 1), &mut out)?;
+            ensure!(n <= budget, "{} unpacks to more than {} bytes", zip_path.display(), max_bytes);
+            budget -= n;
+        }
+    }
+    ensure!(dir.join("manifest.toml").exists(), "{} has no manifest.toml at its root", zip_path.display());
+    Ok(unpacked)
+}
+
+/// Zips the contents of a cart folder (manifest.toml, game.wasm, assets/...) into `zip_path`.
+fn write_cart_zip(cart_dir: &Path, zip_path: &Path) -> Result<()> {
+    use std::io::Write;
+
+    fn add_dir(zw: &mut zip::ZipWriter<fs::File>, root: &Path, dir: &Path) -> Result<()> {
+        let opts = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            // zip names always use '/'
+            let name = path.strip_prefix(root)?.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if entry.file_type()?.is_dir() {
+                zw.add_directory(format!("{name}/"), opts)?;
+                add_dir(zw, root, &path)?;
+            } else {
+                zw.start_file(name, opts)?;
+                zw.write_all(&fs::read(&path)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    if let Some(parent) = zip_path.parent() { fs::create_dir_all(parent)?; }
+    let mut zw = zip::ZipWriter::new(fs::File::create(zip_path)?);
+    add_dir(&mut zw, cart_dir, cart_dir)?;
+    zw.finish()?;
+    Ok(())
+}
+
+/// Save file lives next to the .cart folder or zip (so `pack` never wipes it),
+/// keyed by the manifest title.
+fn save_path_for(cart_dir: &Path, title: Option<&str>) -> PathBuf {
+    let fallback = cart_dir.file_stem().and_then(|s| s.to_str()).unwrap_or("game");
+    let key: String = title.unwrap_or(fallback)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let parent = cart_dir.canonicalize().ok()
+        .and_then(|d| d.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."));
+    parent.join(format!("{key}.sav"))
+}
+
+fn cmd_watch(game_dir: String, out: Option<String>, opts: RunOpts) -> Result<()> {
+    let game = PathBuf::from(&game_dir);
+    let cart_dir = out.as_ref().map(PathBuf::from).unwrap_or_else(|| game.join("build/cart"));
+    cmd_pack(game_dir, out, false, false, false)?;
+
+    // winit needs the main thread, so the watcher rebuilds in the background and the
+    // runtime's file watcher (notify, debounced) reloads the new game.wasm.
+    let cart_wasm = cart_dir.join("game.wasm");
+    thread::spawn(move || {
+        if let Err(e) = watch_and_rebuild(&game, &cart_wasm) {
+            eprintln!("⚠️  oxido watch stopped: {e:#}");
+        }
+    });
+
+    cmd_run(cart_dir.to_string_lossy().into_owned(), opts)
+}
+
+/// Quiet period after the last change before rebuilding (editors save in bursts).
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+fn watch_and_rebuild(game: &Path, cart_wasm: &Path) -> Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let std::result::Result::Ok(ev) = res {
+            if !ev.kind.is_access() { let _ = tx.send(()); }
+        }
+    })?;
+    watcher.watch(&game.join("src"), RecursiveMode::Recursive)?;
+    println!("👀 Watching {} for changes", game.join("src").display());
+
+    while rx.recv().is_ok() {
+        // debounce: wait until the burst of events settles
+        while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+
+        match build_wasm(game) {
+            std::result::Result::Ok(wasm) => {
+                // copy + rename so the runtime never sees a half-written module
+                let tmp = cart_wasm.with_extension("wasm.tmp");
+                fs::copy(&wasm, &tmp)?;
+                fs::rename(&tmp, cart_wasm)?;
+                println!("✅ Rebuilt; the runtime will hot-reload {}", cart_wasm.display());
+            }
+            Err(e) => eprintln!("❌ Build failed: {e:#}"),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_export_web(cart: String, out: Option<String>) -> Result<()> {
+    let p = Path::new(&cart);
+    let unpacked = if p.is_file() { Some(extract_cart_zip(p)?) } else { None };
+    let dir = unpacked.as_ref().map_or(p, UnpackedDir::path).to_path_buf();
+    let manifest_path = dir.join("manifest.toml");
+    let src = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Could not be read {}", manifest_path.display()))?;
+    let man = load_manifest(&src, |f| dir.join(f).is_file())?;
+
+    let out_dir = match out {
+        Some(o) => PathBuf::from(o),
+        None => p.parent().unwrap_or(Path::new(".")).join("web"),
+    };
+    fs::create_dir_all(&out_dir)?;
+
+    let wasm = man.wasm_name();
+    let wasm_file = Path::new(&wasm).file_name().context("`wasm` has no file name")?;
+    fs::copy(dir.join(&wasm), out_dir.join(wasm_file))?;
+
+    let title = man.title.clone().unwrap_or_else(|| "OxidoBoy".to_string());
+    let html = include_str!("../templates/web/index.html")
+        .replace("{{TITLE}}", &html_escape(&title))
+        .replace("{{TITLE_JS}}", &js_escape(&title))
+        .replace("{{WASM}}", &wasm_file.to_string_lossy())
+        .replace("{{WIDTH}}", &man.width.unwrap_or(160).to_string())
+        .replace("{{HEIGHT}}", &man.height.unwrap_or(144).to_string())
+        .replace("{{SCALE}}", &man.scale.unwrap_or(3).to_string());
+    fs::write(out_dir.join("index.html"), html)?;
+    fs::write(out_dir.join("oxido.js"), include_str!("../templates/web/oxido.js"))?;
+
+    println!("🌐 Web build at {}", out_dir.display());
+    println!("   Serve it over http (e.g. `python3 -m http.server -d {}`), or zip the folder for itch.io.", out_dir.display());
+    println!("   Audio isn't supported in the browser yet.");
+    Ok(())
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Contents for a double-quoted JS string inside a <script> tag.
+fn js_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('<', "\\x3c")
+}
+
+fn cmd_info(path: String) -> Result<()> {
+    let p = Path::new(&path);
+    let (manifest_src, files) = read_cart_listing(p)?;
+    let man = Manifest::parse(&manifest_src)?;
+
+    let mut warnings = Vec::new();
+    for (field, present) in [
+        ("title", man.title.is_some()),
+        ("version", man.version.is_some()),
+        ("width", man.width.is_some()),
+        ("height", man.height.is_some()),
+    ] {
+        if !present { warnings.push(format!("missing required field `{field}`")); }
+    }
+    for key in unknown_manifest_keys(&manifest_src) {
+        warnings.push(format!("unknown field `{key}` (ignored)"));
+    }
+    warnings.extend(man.problems(|f| files.iter().any(|(n, _)| n == f)));
+
+    let wasm_name = man.wasm_name();
+    let wasm_size = files.iter().find(|(n, _)| *n == wasm_name).map(|(_, s)| *s);
+
+    let or_unset = |v: Option<String>| v.unwrap_or_else(|| "(not set)".to_string());
+    println!("Title:      {}", or_unset(man.title));
+    println!("Version:    {}", or_unset(man.version));
+    if let Some(author) = man.author { println!("Author:     {author}"); }
+    if let Some(description) = man.description { println!("About:      {description}"); }
+    if let Some(icon) = &man.icon {
+        let found = files.iter().any(|(n, _)| n == icon);
+        println!("Icon:       {icon}{}", if found { "" } else { " (missing)" });
     }
+    println!("Resolution: {}x{}", man.width.unwrap_or(160), man.height.unwrap_or(144));
+    println!("Scale:      {}", man.scale.map(|s| s.to_string()).unwrap_or_else(|| "(default)".into()));
+    println!("Filter:     {}", man.filter.unwrap_or_else(|| "none".into()));
+    match wasm_size {
+        Some(size) => println!("Wasm:       {wasm_name} ({})", human_size(size)),
+        None => println!("Wasm:       {wasm_name} (missing)"),
+    }
+
+    let assets: Vec<_> = files.iter().filter(|(n, _)| *n != "manifest.toml" && *n != wasm_name).collect();
+    if assets.is_empty() {
+        println!("Assets:     none");
+    } else {
+        let total: u64 = assets.iter().map(|(_, s)| s).sum();
+        println!("Assets:     {} files, {}", assets.len(), human_size(total));
+        let width = assets.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+        for (name, size) in assets {
+            println!("  {name:<width$}  {:>10}", human_size(*size));
+        }
+    }
+
+    for w in &warnings {
+        eprintln!("⚠️  {w}");
+    }
+    Ok(())
+}
 
+/// Reads `manifest.toml` and lists every file (path with '/', size in bytes)
+/// of a .cart folder or zip, without extracting anything.
+fn read_cart_listing(p: &Path) -> Result<(String, Vec<(String, u64)>)> {
     if p.is_dir() {
-        // Upload .cart folder manifest
+        fn walk(root: &Path, dir: &Path, out: &mut Vec<(String, u64)>) -> Result<()> {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if entry.file_type()?.is_dir() {
+                    walk(root, &path, out)?;
+                } else {
+                    let name = path.strip_prefix(root)?.components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    out.push((name, entry.metadata()?.len()));
+                }
+            }
+            Ok(())
+        }
         let manifest_path = p.join("manifest.toml");
-        let s = fs::read_to_string(&manifest_path)
+        let src = fs::read_to_string(&manifest_path)
             .with_context(|| format!("Could not be read {}", manifest_path.display()))?;
-        let man: Manifest = toml::from_str(&s)
-            .context("manifest.toml invalid")?;
-
-        let w = man.width.unwrap_or(width);
-        let h = man.height.unwrap_or(height);
-        let s = man.scale.unwrap_or(scale);  
-        let wasm_name = man.wasm.unwrap_or_else(|| "game.wasm".to_string());
-        let wasm_path = p.join(wasm_name);
+        let mut files = Vec::new();
+        walk(p, p, &mut files)?;
+        files.sort();
+        return Ok((src, files));
+    }
 
-        return run(Cartridge { wasm_path, w, h , scale: s});
+    ensure!(p.is_file(), "PATH must be a .cart folder or a .cart zip");
+    let mut archive = zip::ZipArchive::new(fs::File::open(p)?)
+        .with_context(|| format!("{} is not a valid .cart zip", p.display()))?;
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if !entry.is_dir() { files.push((entry.name().to_string(), entry.size())); }
     }
+    files.sort();
+    let mut src = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name("manifest.toml").context("no manifest.toml in the archive")?, &mut src)?;
+    Ok((src, files))
+}
 
-    bail!("PATH must be a .wasm or a folder .cart");
+fn human_size(bytes: u64) -> String {
+    if bytes < 1024 { format!("{bytes} B") } else { format!("{:.1} KB", bytes as f64 / 1024.0) }
 }
 
-fn cmd_new(name: String) -> Result<()> {
+fn cmd_new(name: String, template: Template) -> Result<()> {
     let root = PathBuf::from(&name);
     let src_dir = root.join("src");
     let cart_dir = root.join("cart").join("assets");
@@ -117,106 +900,89 @@ oxido_sdk = {{ path = "../../oxido_sdk" }}
 "#);
     fs::write(root.join("Cargo.toml"), cargo_toml)?;
 
-    // src/lib.rs (minimal game)
-    let lib_rs = r#"use oxido_sdk::*;
-
-static mut FB: [u8; DEFAULT_W * DEFAULT_H * 4] = [0; DEFAULT_W * DEFAULT_H * 4];
-static mut INPUT_BITS: u32 = 0;
-static mut X: f32 = 10.0;
-
-const SPEED: f32 = 60.0;
-
-#[no_mangle]
-pub extern "C" fn oxido_init() {}
-
-#[no_mangle]
-pub extern "C" fn oxido_update(dt_ms: f32) {
-    let dt = dt_ms / 1000.0;
-    unsafe {
-        if INPUT_BITS & key_bit(Key::Right) != 0 { X += SPEED * dt; }
-        if INPUT_BITS & key_bit(Key::Left)  != 0 { X -= SPEED * dt; }
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn oxido_draw_ptr() -> *const u8 {
-    unsafe {
-        let mut f = Frame { data: &mut FB, w: DEFAULT_W, h: DEFAULT_H };
-        f.clear(P0);
-        f.rect(X as i32, 60, 16, 16, P3);
-        FB.as_ptr()
-    }
-}
-
-#[no_mangle] pub extern "C" fn oxido_draw_len() -> usize { DEFAULT_W * DEFAULT_H * 4 }
-#[no_mangle] pub extern "C" fn oxido_input_set(bits: u32) { unsafe { INPUT_BITS = bits; } }
-"#;
+    // src/lib.rs for the chosen template
+    let lib_rs = match template {
+        Template::Minimal => include_str!("../templates/minimal.rs"),
+        Template::Tilemap => include_str!("../templates/tilemap.rs"),
+        Template::Audio => include_str!("../templates/audio.rs"),
+    };
     fs::write(src_dir.join("lib.rs"), lib_rs)?;
 
     // default manifest.toml
-    
-    let manifest = r#"title = "My Oxido Game"
+    let title = match template {
+        Template::Minimal => "My Oxido Game",
+        Template::Tilemap => "My Tilemap Game",
+        Template::Audio => "My Audio Demo",
+    };
+    let manifest = format!(r#"title = "{title}"
 version = "0.1.0"
 width = 160
 height = 144
 scale = 3
 wasm = "game.wasm"
-"#;
+# author = "Your Name"
+# description = "One line about the game"
+# icon = "assets/icon.png"
+"#);
     fs::write(root.join("cart").join("manifest.toml"), manifest)?;
 
+    let steps = [
+        "rustup target add wasm32-unknown-unknown  # if you don't have it",
+        "cargo build --release --target wasm32-unknown-unknown",
+        "oxido pack .",
+        "oxido run build/cart   # run the folder .cart",
+    ];
+
+    // build outputs stay out of git; lines already there (a re-run) aren't repeated
+    let gitignore = root.join(".gitignore");
+    let existing = fs::read_to_string(&gitignore).unwrap_or_default();
+    let missing: Vec<&str> = ["/target", "/build"].into_iter()
+        .filter(|l| !existing.lines().any(|e| e.trim() == *l))
+        .collect();
+    if !missing.is_empty() {
+        let sep = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+        fs::write(&gitignore, format!("{existing}{sep}{}\n", missing.join("\n")))?;
+    }
+
+    // README with the same steps; an existing one is the user's, leave it
+    let readme = root.join("README.md");
+    if !readme.exists() {
+        let mut text = format!("# {title}\n\nA game for the OxidoBoy fantasy console.\n\n## Build and run\n\n```bash\n");
+        for s in &steps { text += &format!("{s}\n"); }
+        text += "```\n\nGame code is in `src/lib.rs`; the cart's manifest and assets are in `cart/`.\n";
+        fs::write(&readme, text)?;
+    }
+
     println!("✅ Game created in ./{name}");
     println!("Next:");
     println!("  1) cd {name}");
-    println!("  2) rustup target add wasm32-unknown-unknown  # if you don't have it");
-    println!("  3) cargo build --release --target wasm32-unknown-unknown");
-    println!("  4) oxido pack .");
-    println!("  5) oxido run build/cart   # run the folder .cart");
+    for (i, s) in steps.iter().enumerate() {
+        println!("  {}) {s}", i + 2);
+    }
     Ok(())
 }
 
-fn cmd_pack(game_dir: String, out: Option<String>) -> Result<()> {
+fn cmd_build(game_dir: String) -> Result<()> {
     let game = PathBuf::from(&game_dir);
-    let cargo_toml = game.join("Cargo.toml");
-    ensure!(cargo_toml.exists(), "Not found {}", cargo_toml.display());
-
-    // Read the package name to locate the generated .wasm
-    let cargo_str = fs::read_to_string(&cargo_toml)?;
-    let pkg_name = parse_package_name(&cargo_str)
-        .context("Could not find [package].name in Cargo.toml")?;
-
-    // Compile to wasm (release)
-    let status = Command::new("cargo")
-        .arg("build")
-        .arg("--release")
-        .arg("--target").arg("wasm32-unknown-unknown")
-        .current_dir(&game)
-        .status()?;
-    ensure!(status.success(), "Game compilation failed");
-
-    // Paths: in workspace, the artifacts go to <workspace>/target; outside, to <game>/target
-    let ws_root = find_workspace_root(&game);
-    let target_base = ws_root.unwrap_or_else(|| game.clone()).join("target");
-
-    // Try first in workspace target, then in game's local target
-    let candidate_a = target_base.join("wasm32-unknown-unknown/release")
-        .join(format!("{pkg_name}.wasm"));
-    let candidate_b = game.join("target/wasm32-unknown-unknown/release")
-        .join(format!("{pkg_name}.wasm"));
+    let wasm = build_wasm(&game)?;
+    let size = fs::metadata(&wasm).map(|m| m.len()).unwrap_or(0);
+    println!("✅ Built {} ({:.1} KB)", wasm.display(), size as f64 / 1024.0);
+    println!("To package: oxido pack {game_dir} --no-build");
+    Ok(())
+}
 
-    let wasm_src = if candidate_a.exists() {
-        candidate_a
-    } else if candidate_b.exists() {
-        candidate_b
+fn cmd_pack(game_dir: String, out: Option<String>, no_build: bool, zip: bool, optimize: bool) -> Result<()> {
+    let game = PathBuf::from(&game_dir);
+    let wasm_src = if no_build {
+        let pkg_name = read_package_name(&game)?;
+        find_wasm_artifact(&game, &pkg_name)?
     } else {
-        bail!(
-            "Could not find wasm.\nSearched:\n  - {}\n  - {}",
-            candidate_a.display(),
-            candidate_b.display()
-        );
+        build_wasm(&game)?
     };
 
-    // .cart output
-    let out_dir = out.map(PathBuf::from)
+    // .cart output (with --zip, --out names the archive and the folder is only staging)
+    let zip_out = zip.then(|| out.clone().map(PathBuf::from).unwrap_or_else(|| game.join("build/game.cart")));
+    let out_dir = out.filter(|_| !zip).map(PathBuf::from)
         .unwrap_or_else(|| game.join("build/cart"));
     if out_dir.exists() {
         fs::remove_dir_all(&out_dir)?;
@@ -228,18 +994,29 @@ fn cmd_pack(game_dir: String, out: Option<String>) -> Result<()> {
     let manifest = if manifest_src.exists() {
         fs::read_to_string(&manifest_src)?
     } else {
+        let pkg_name = read_package_name(&game)?;
         format!(r#"title = "{pkg}"
 version = "0.1.0"
 width = 160
 height = 144
 scale = 3
 wasm = "game.wasm"
+# author = "Your Name"
+# description = "One line about the game"
+# icon = "assets/icon.png"
 "#, pkg=pkg_name)
     };
-    fs::write(out_dir.join("manifest.toml"), manifest)?;
+    let man = Manifest::parse(&manifest)?;
+    fs::write(out_dir.join("manifest.toml"), &manifest)?;
 
     // copy the wasm as game.wasm
-    fs::copy(&wasm_src, out_dir.join("game.wasm"))?;
+    let wasm_dst = out_dir.join("game.wasm");
+    fs::copy(&wasm_src, &wasm_dst)?;
+    if optimize {
+        optimize_wasm(&wasm_dst)?;
+    }
+    let wasm = fs::read(&wasm_dst)?;
+    println!("📦 game.wasm: {} ({} gzipped)", human_size(wasm.len() as u64), human_size(gzip_len(&wasm)));
 
     // copy assets if they exist
     let assets_src = game.join("cart/assets");
@@ -247,12 +1024,132 @@ wasm = "game.wasm"
     if assets_src.exists() {
         copy_dir_recursive(&assets_src, &assets_dst)?;
     }
-    
+
+    // PNG -> indexed atlases listed under [assets], next to game.wasm
+    for name in assets::build_atlases(&game.join("cart"), &man.assets, &out_dir)? {
+        println!("🎨 {name}");
+    }
+
+    // catch what `oxido run` would reject before shipping the cart
+    load_manifest(&manifest, |f| out_dir.join(f).is_file())?;
+
+    if let Some(zip_path) = zip_out {
+        write_cart_zip(&out_dir, &zip_path)?;
+        println!("✅ Cartridge generated in {}", zip_path.display());
+        println!("To run: oxido run {}", zip_path.display());
+        return Ok(());
+    }
+
     println!("✅ Cartridge generated in {}", out_dir.display());
     println!("To run: oxido run {}", out_dir.display());
     Ok(())
 }
 
+/// Runs `wasm-opt -Oz` over `wasm` in place. A missing or failing wasm-opt
+/// only warns: the unoptimized file is still a valid cart.
+fn optimize_wasm(wasm: &Path) -> Result<()> {
+    let before = fs::metadata(wasm)?.len();
+    let tmp = wasm.with_extension("opt.wasm");
+    let status = Command::new("wasm-opt").arg("-Oz").arg(wasm).arg("-o").arg(&tmp).status();
+    match status {
+        std::result::Result::Ok(s) if s.success() => {
+            fs::rename(&tmp, wasm)?;
+            let after = fs::metadata(wasm)?.len();
+            println!("🪶 wasm-opt -Oz: {} → {}", human_size(before), human_size(after));
+        }
+        std::result::Result::Ok(s) => {
+            let _ = fs::remove_file(&tmp);
+            eprintln!("⚠️  wasm-opt failed ({s}); packing the unoptimized game.wasm");
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("⚠️  --optimize: wasm-opt is not on PATH (it comes with Binaryen); skipping");
+        }
+        Err(e) => eprintln!("⚠️  --optimize: could not run wasm-opt ({e}); skipping"),
+    }
+    Ok(())
+}
+
+/// Size of `bytes` after gzip, roughly what a web server sends.
+fn gzip_len(bytes: &[u8]) -> u64 {
+    let mut enc = GzEncoder::new(Vec::new(), Compression::best());
+    enc.write_all(bytes).and_then(|()| enc.finish()).map_or(0, |v| v.len() as u64)
+}
+
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// Compiles the game crate for wasm32 (release) and returns the artifact path.
+fn build_wasm(game: &Path) -> Result<PathBuf> {
+    let pkg_name = read_package_name(game)?;
+    ensure_wasm_target()?;
+
+    println!("🔨 Building {pkg_name} ({WASM_TARGET}, release)...");
+    let status = Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--target").arg(WASM_TARGET)
+        .current_dir(game)
+        .status()?;
+    ensure!(status.success(), "Game compilation failed");
+
+    find_wasm_artifact(game, &pkg_name)
+}
+
+fn read_package_name(game: &Path) -> Result<String> {
+    let cargo_toml = game.join("Cargo.toml");
+    ensure!(cargo_toml.exists(), "Not found {}", cargo_toml.display());
+    let cargo_str = fs::read_to_string(&cargo_toml)?;
+    parse_package_name(&cargo_str).context("Could not find [package].name in Cargo.toml")
+}
+
+/// Fails with a `rustup target add` hint if the wasm target is missing.
+/// Without rustup (distro or standalone toolchains) the target's std is
+/// looked for in rustc's sysroot instead; if even that can't be found,
+/// cargo gets to decide.
+fn ensure_wasm_target() -> Result<()> {
+    let Some(out) = Command::new("rustup").args(["target", "list", "--installed"]).output().ok() else {
+        let sysroot = Command::new("rustc").args(["--print", "sysroot"]).output().ok()
+            .filter(|o| o.status.success())
+            .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()));
+        if let Some(root) = sysroot.filter(|r| r.join("lib/rustlib").is_dir()) {
+            ensure!(root.join("lib/rustlib").join(WASM_TARGET).is_dir(),
+                "The {WASM_TARGET} target is not installed in {} (a toolchain without rustup).\n\
+                 Install its standard library the way Rust was installed (e.g. the distro's wasm32 std package)\n\
+                 or switch to a rustup toolchain and run:\n  rustup target add {WASM_TARGET}",
+                root.display());
+        }
+        return Ok(());
+    };
+    if out.status.success() && !String::from_utf8_lossy(&out.stdout).lines().any(|l| l.trim() == WASM_TARGET) {
+        bail!("The {WASM_TARGET} target is not installed.\nInstall it with:\n  rustup target add {WASM_TARGET}");
+    }
+    Ok(())
+}
+
+/// Locates `<pkg>.wasm`: in a workspace the artifacts go to <workspace>/target,
+/// outside one to <game>/target.
+fn find_wasm_artifact(game: &Path, pkg_name: &str) -> Result<PathBuf> {
+    let ws_root = find_workspace_root(game);
+    let target_base = ws_root.unwrap_or_else(|| game.to_path_buf()).join("target");
+    // cargo turns dashes in the crate name into underscores
+    let file = format!("{}.wasm", pkg_name.replace('-', "_"));
+
+    // Try first in workspace target, then in game's local target
+    let candidate_a = target_base.join(WASM_TARGET).join("release").join(&file);
+    let candidate_b = game.join("target").join(WASM_TARGET).join("release").join(&file);
+
+    if candidate_a.exists() {
+        Ok(candidate_a)
+    } else if candidate_b.exists() {
+        Ok(candidate_b)
+    } else {
+        bail!(
+            "Could not find wasm.\nSearched:\n  - {}\n  - {}",
+            candidate_a.display(),
+            candidate_b.display()
+        );
+    }
+}
+
 fn parse_package_name(cargo_toml: &str) -> Option<String> {
     #[derive(Deserialize)]
     struct Pkg { name: String }
@@ -267,10 +1164,8 @@ fn find_workspace_root(start: &Path) -> Option<PathBuf> {
         let toml = dir.join("Cargo.toml");
         if toml.exists() {
             match std::fs::read_to_string(&toml) {
-                std::result::Result::Ok(s) => {
-                    if s.contains("[workspace]") {
-                        return Some(dir.to_path_buf());
-                    }
+                std::result::Result::Ok(s) if s.contains("[workspace]") => {
+                    return Some(dir.to_path_buf());
                 }
                 _ => { /* ignore reading errors and continue climbing */ }
             }
@@ -294,3 +1189,88 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh, empty folder under the system temp dir for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oxido-cli-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn zipped_cart_is_capped_and_cleaned_up() {
+        let root = scratch("zip");
+        let cart = root.join("game.cart");
+        fs::create_dir_all(cart.join("assets")).unwrap();
+        fs::write(cart.join("manifest.toml"), "wasm = \"game.wasm\"\n").unwrap();
+        fs::write(cart.join("assets/big.bin"), vec![0u8; 4096]).unwrap();
+        let zip = root.join("game.zip");
+        write_cart_zip(&cart, &zip).unwrap();
+
+        let dir = extract_cart_zip_capped(&zip, 8192).unwrap();
+        let path = dir.path().to_path_buf();
+        assert_eq!(fs::read(path.join("assets/big.bin")).unwrap().len(), 4096);
+        drop(dir);
+        assert!(!path.exists());
+
+        // the 4 KiB of zeros compress to almost nothing but still count in full
+        let err = extract_cart_zip_capped(&zip, 4000).err().unwrap();
+        assert!(err.to_string().contains("more than 4000 bytes"), "{err}");
+        assert!(!path.exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn export_web_references_the_copied_wasm() {
+        let root = scratch("web");
+        let cart = root.join("game.cart");
+        fs::create_dir_all(cart.join("build")).unwrap();
+        fs::write(cart.join("manifest.toml"), "title = \"Tom & \\\"Jerry\\\"\"\nwasm = \"build/game.wasm\"\nwidth = 96\n").unwrap();
+        fs::write(cart.join("build/game.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        let out = root.join("out");
+        cmd_export_web(cart.to_string_lossy().into(), Some(out.to_string_lossy().into())).unwrap();
+
+        // the wasm lands next to the page under its bare file name
+        assert_eq!(fs::read(out.join("game.wasm")).unwrap(), b"\0asm\x01\0\0\0");
+        let html = fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(html.contains(r#"wasm: "game.wasm""#), "{html}");
+        assert!(html.contains("width: 96, height: 144"), "{html}");
+        assert!(html.contains("<title>Tom &amp; &quot;Jerry&quot;</title>"), "{html}");
+        assert!(html.contains(r#"<script src="oxido.js"></script>"#));
+        assert!(!html.contains("{{"));
+        // and the harness loads whatever the page names
+        let js = fs::read_to_string(out.join("oxido.js")).unwrap();
+        assert!(js.contains("fetch(cfg.wasm)"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn relaunch_swaps_only_the_cart_path() {
+        let argv = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        let cart = Path::new("games/a.cart");
+        assert_eq!(
+            relaunch_args(&argv(&["oxido", "run", "--record-audio", ".", ".", "-s", "2"]), cart).unwrap(),
+            argv(&["run", "--record-audio", ".", "games/a.cart", "-s", "2"])
+        );
+        assert_eq!(
+            relaunch_args(&argv(&["oxido", "run", "--speed=2", "games", "--filter", "crt"]), cart).unwrap(),
+            argv(&["run", "--speed=2", "games/a.cart", "--filter", "crt"])
+        );
+        assert!(relaunch_args(&argv(&["oxido", "info", "games"]), cart).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relaunch_keeps_non_utf8_arguments() {
+        use std::os::unix::ffi::OsStringExt;
+        let odd = OsString::from_vec(b"take\xff.wav".to_vec());
+        let argv = vec!["oxido".into(), "run".into(), "games".into(), "--record-audio".into(), odd.clone()];
+        let args = relaunch_args(&argv, Path::new("games/a.cart")).unwrap();
+        assert_eq!(args, [OsString::from("run"), "games/a.cart".into(), "--record-audio".into(), odd]);
+    }
+}
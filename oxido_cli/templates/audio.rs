@@ -0,0 +1,129 @@
+use oxido_sdk::*;
+use std::ptr::{addr_of, addr_of_mut};
+
+static mut FB: [u8; DEFAULT_W * DEFAULT_H * 4] = [0; DEFAULT_W * DEFAULT_H * 4];
+static mut INPUT_BITS: u32 = 0;
+static mut INPUT: InputState = InputState::new();
+
+// ===================== AUDIO (status exported to host) ======================
+// Layout must match WireCh on host (16 fields x 4 bytes)
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct AudioCh {
+    kind: u32,     // 0=pulse,1=pulse,2=noise,7=wavetable
+    base_freq: f32,
+    vol:  f32,     // 0..1
+    duty: f32,     // pulse
+    gate: u32,     // 1=on
+    a_ms: f32, d_ms: f32, s_lvl: f32, r_ms: f32,
+    arp_a: i32, arp_b: i32, arp_c: i32, arp_rate_hz: f32,
+    env_curve: u32, // 0=linear, 1=exp
+    portamento_ms: f32, // glide between notes while the gate stays on
+    noise_width: u32,   // noise LFSR: 15 bits (hiss) or 7 (metallic)
+}
+const SILENT: AudioCh = AudioCh {
+    kind: 0, base_freq: 0.0, vol: 0.0, duty: 0.5, gate: 0,
+    a_ms: 0.0, d_ms: 0.0, s_lvl: 0.0, r_ms: 0.0,
+    arp_a: 0, arp_b: 0, arp_c: 0, arp_rate_hz: 0.0,
+    env_curve: 0, portamento_ms: 0.0, noise_width: 15,
+};
+static mut AUDIO_STATE: [AudioCh; 4] = [SILENT; 4];
+
+#[no_mangle]
+pub extern "C" fn oxido_audio_state_ptr() -> *const u8 {
+    addr_of!(AUDIO_STATE) as *const u8
+}
+#[no_mangle]
+pub extern "C" fn oxido_audio_state_len() -> usize {
+    core::mem::size_of::<AudioCh>() * 4
+}
+
+// --- ADSR editor -------------------------------------------------------------
+// Up/Down pick a parameter, Left/Right change it, A plays a note, B a noise hit,
+// Start switches the envelope curve.
+const PARAMS: [(&str, f32, f32, f32); 4] = [
+    // name, step, min, max
+    ("ATTACK",  10.0, 0.0, 1000.0),
+    ("DECAY",   10.0, 0.0, 1000.0),
+    ("SUSTAIN", 0.05, 0.0, 1.0),
+    ("RELEASE", 10.0, 0.0, 2000.0),
+];
+static mut SELECTED: usize = 0;
+
+fn param(ch: &mut AudioCh, i: usize) -> &mut f32 {
+    match i {
+        0 => &mut ch.a_ms,
+        1 => &mut ch.d_ms,
+        2 => &mut ch.s_lvl,
+        _ => &mut ch.r_ms,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_init() {
+    unsafe {
+        AUDIO_STATE[0] = AudioCh {
+            kind: 0, base_freq: 440.0, vol: 0.35, duty: 0.5,
+            a_ms: 20.0, d_ms: 120.0, s_lvl: 0.4, r_ms: 300.0, ..SILENT
+        };
+        AUDIO_STATE[2] = AudioCh {
+            kind: 2, base_freq: 2200.0, vol: 0.25,
+            d_ms: 40.0, r_ms: 60.0, ..SILENT
+        };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_update(_dt_ms: f32) {
+    unsafe {
+        let input = &mut *addr_of_mut!(INPUT);
+        let audio = &mut *addr_of_mut!(AUDIO_STATE);
+        input.update(INPUT_BITS);
+        let held = |k: Key| input.held(k);
+
+        if input.just_pressed(Key::Up)   { SELECTED = (SELECTED + PARAMS.len() - 1) % PARAMS.len(); }
+        if input.just_pressed(Key::Down) { SELECTED = (SELECTED + 1) % PARAMS.len(); }
+
+        let (_, step, min, max) = PARAMS[SELECTED];
+        let v = param(&mut audio[0], SELECTED);
+        if held(Key::Left)  { *v = (*v - step).max(min); }
+        if held(Key::Right) { *v = (*v + step).min(max); }
+
+        if input.just_pressed(Key::Start) { audio[0].env_curve ^= 1; }
+        audio[0].gate = held(Key::A) as u32;
+        audio[2].gate = held(Key::B) as u32;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_draw_ptr() -> *const u8 {
+    unsafe {
+        let mut f = Frame { data: &mut *addr_of_mut!(FB), w: DEFAULT_W, h: DEFAULT_H };
+        let audio = &mut *addr_of_mut!(AUDIO_STATE);
+        f.clear(P0);
+        f.text5x7(4, 4, "ADSR DEMO", P3);
+        let curve = if audio[0].env_curve == 1 { "EXP" } else { "LIN" };
+        f.text5x7(100, 4, curve, P2);
+
+        for (i, &(name, _, _, max)) in PARAMS.iter().enumerate() {
+            let y = 20 + i as i32 * 16;
+            let color = if i == SELECTED { P3 } else { P2 };
+            let v = *param(&mut audio[0], i);
+            f.text5x7(4, y, name, color);
+            f.rect(60, y, 96, 7, P1);
+            f.rect(60, y, (v / max * 96.0) as i32, 7, color);
+        }
+
+        // gate indicator
+        let lit = if audio[0].gate != 0 { P3 } else { P1 };
+        f.rect(4, 92, 12, 12, lit);
+        f.text5x7(22, 95, "Z=NOTE X=NOISE", P2);
+        f.text5x7(4, 112, "UP/DOWN SELECT", P2);
+        f.text5x7(4, 122, "LEFT/RIGHT EDIT", P2);
+        f.text5x7(4, 132, "ENTER CURVE", P2);
+        addr_of!(FB) as *const u8
+    }
+}
+
+#[no_mangle] pub extern "C" fn oxido_draw_len() -> usize { DEFAULT_W * DEFAULT_H * 4 }
+#[no_mangle] pub extern "C" fn oxido_input_set(bits: u32) { unsafe { INPUT_BITS = bits; } }
@@ -0,0 +1,33 @@
+use oxido_sdk::*;
+use std::ptr::{addr_of, addr_of_mut};
+
+static mut FB: [u8; DEFAULT_W * DEFAULT_H * 4] = [0; DEFAULT_W * DEFAULT_H * 4];
+static mut INPUT_BITS: u32 = 0;
+static mut X: f32 = 10.0;
+
+const SPEED: f32 = 60.0;
+
+#[no_mangle]
+pub extern "C" fn oxido_init() {}
+
+#[no_mangle]
+pub extern "C" fn oxido_update(dt_ms: f32) {
+    let dt = dt_ms / 1000.0;
+    unsafe {
+        if INPUT_BITS & key_bit(Key::Right) != 0 { X += SPEED * dt; }
+        if INPUT_BITS & key_bit(Key::Left)  != 0 { X -= SPEED * dt; }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_draw_ptr() -> *const u8 {
+    unsafe {
+        let mut f = Frame { data: &mut *addr_of_mut!(FB), w: DEFAULT_W, h: DEFAULT_H };
+        f.clear(P0);
+        f.rect(X as i32, 60, 16, 16, P3);
+        addr_of!(FB) as *const u8
+    }
+}
+
+#[no_mangle] pub extern "C" fn oxido_draw_len() -> usize { DEFAULT_W * DEFAULT_H * 4 }
+#[no_mangle] pub extern "C" fn oxido_input_set(bits: u32) { unsafe { INPUT_BITS = bits; } }
@@ -0,0 +1,111 @@
+use oxido_sdk::*;
+use std::ptr::{addr_of, addr_of_mut};
+use std::sync::OnceLock;
+
+static mut FB: [u8; DEFAULT_W * DEFAULT_H * 4] = [0; DEFAULT_W * DEFAULT_H * 4];
+static mut INPUT_BITS: u32 = 0;
+
+// player position in world pixels
+static mut PX: f32 = 24.0;
+static mut PY: f32 = 24.0;
+
+const SPEED: f32 = 60.0;
+const PLAYER: i32 = 8;
+
+// --- Atlas (4 tiles of 8x8 in a 16x16 image) ---------------------------------
+fn build_atlas() -> SpriteAtlas {
+    let (w, h, tile) = (16usize, 16usize, 8usize);
+    let mut px = vec![0u8; w * h];
+    let mut put_tile = |tx: usize, ty: usize, f: &dyn Fn(usize, usize) -> u8| {
+        for y in 0..tile { for x in 0..tile {
+            px[(ty * tile + y) * w + tx * tile + x] = f(x, y) & 0b11;
+        }}
+    };
+    put_tile(0, 0, &|x, y| if (x + y) % 6 == 0 { 2 } else { 1 });          // 0: floor
+    put_tile(1, 0, &|x, y| if x == 0 || y == 0 { 2 } else { 3 });          // 1: wall
+    put_tile(0, 1, &|x, y| if (2..6).contains(&x) && (2..6).contains(&y) { 3 } else { 0 }); // 2: player
+    put_tile(1, 1, &|x, y| if (x ^ y) & 1 == 0 { 2 } else { 1 });          // 3: path
+    SpriteAtlas::from_indexed(px, w, h, tile, tile)
+}
+static ATLAS: OnceLock<SpriteAtlas> = OnceLock::new();
+fn atlas() -> &'static SpriteAtlas { ATLAS.get_or_init(build_atlas) }
+
+// --- Map (40x36 tiles, bigger than the screen) -------------------------------
+const MAP_W: usize = 40;
+const MAP_H: usize = 36;
+fn build_map() -> TileMap {
+    let mut tiles = vec![0usize; MAP_W * MAP_H];
+    for y in 0..MAP_H { for x in 0..MAP_W {
+        let border = x == 0 || y == 0 || x == MAP_W - 1 || y == MAP_H - 1;
+        let pillar = x % 8 == 4 && y % 6 == 3;
+        tiles[y * MAP_W + x] = if border || pillar { 1 } else if y % 6 == 0 { 3 } else { 0 };
+    }}
+    TileMap::new(MAP_W, MAP_H, 8, 8, tiles)
+}
+static MAP: OnceLock<TileMap> = OnceLock::new();
+fn map() -> &'static TileMap { MAP.get_or_init(build_map) }
+
+fn solid_at(wx: i32, wy: i32) -> bool {
+    let m = map();
+    let (tx, ty) = (wx.div_euclid(m.tile_w as i32), wy.div_euclid(m.tile_h as i32));
+    if tx < 0 || ty < 0 || tx >= m.w as i32 || ty >= m.h as i32 { return true; }
+    m.tiles[ty as usize * m.w + tx as usize] == 1
+}
+fn box_free(x: f32, y: f32) -> bool {
+    let (x, y) = (x.floor() as i32, y.floor() as i32);
+    [(x, y), (x + PLAYER - 1, y), (x, y + PLAYER - 1), (x + PLAYER - 1, y + PLAYER - 1)]
+        .iter().all(|&(cx, cy)| !solid_at(cx, cy))
+}
+
+// camera: keep the player centered, clamped to the map edges
+fn camera() -> (i32, i32) {
+    let m = map();
+    let (map_w, map_h) = ((m.w * m.tile_w) as i32, (m.h * m.tile_h) as i32);
+    let (view_w, view_h) = (DEFAULT_W as i32, DEFAULT_H as i32);
+    unsafe {
+        let cx = (PX as i32 + PLAYER / 2 - view_w / 2).clamp(0, (map_w - view_w).max(0));
+        let cy = (PY as i32 + PLAYER / 2 - view_h / 2).clamp(0, (map_h - view_h).max(0));
+        (cx, cy)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_init() {
+    let _ = (atlas(), map());
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_update(dt_ms: f32) {
+    let step = SPEED * dt_ms / 1000.0;
+    unsafe {
+        let held = |k: Key| INPUT_BITS & key_bit(k) != 0;
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        if held(Key::Left)  { dx -= step; }
+        if held(Key::Right) { dx += step; }
+        if held(Key::Up)    { dy -= step; }
+        if held(Key::Down)  { dy += step; }
+        // move one axis at a time so walls can be slid along
+        if box_free(PX + dx, PY) { PX += dx; }
+        if box_free(PX, PY + dy) { PY += dy; }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn oxido_draw_ptr() -> *const u8 {
+    unsafe {
+        let mut f = Frame { data: &mut *addr_of_mut!(FB), w: DEFAULT_W, h: DEFAULT_H };
+        let pal = Palette::GB;
+        let (cx, cy) = camera();
+
+        map().draw(&mut f, atlas(), &pal, cx, cy, false);
+        atlas().blit(&mut f, PX as i32 - cx, PY as i32 - cy, 2, &pal, false, false, true);
+
+        f.rect(0, 0, DEFAULT_W as i32, 10, pal.color(0));
+        f.text5x7(2, 2, &format!("X {:3} Y {:3}", PX as i32, PY as i32), pal.color(3));
+        addr_of!(FB) as *const u8
+    }
+}
+
+#[no_mangle] pub extern "C" fn oxido_draw_len() -> usize { DEFAULT_W * DEFAULT_H * 4 }
+#[no_mangle] pub extern "C" fn oxido_input_set(bits: u32) { unsafe { INPUT_BITS = bits; } }
@@ -0,0 +1,139 @@
+// OxidoBoy web harness: runs a cart's wasm in the browser with the same ABI
+// as the native runtime (see the README). Generated by `oxido export-web`.
+// Not implemented here yet: audio, oxido_dirty_rect, fuel limits, hot reload.
+"use strict";
+
+(async () => {
+  const cfg = window.OXIDO;
+  const canvas = document.getElementById("screen");
+  const ctx = canvas.getContext("2d");
+  const errorBox = document.getElementById("error");
+
+  // --- framebuffer -----------------------------------------------------------
+  let fbW = 0, fbH = 0, image = null;
+  function setSize(w, h) {
+    fbW = w; fbH = h;
+    canvas.width = w; canvas.height = h;
+    canvas.style.width = w * cfg.scale + "px";
+    canvas.style.height = h * cfg.scale + "px";
+    image = ctx.createImageData(w, h);
+  }
+  setSize(cfg.width, cfg.height);
+
+  // --- input (same bit layout as the native runtime) ---------------------------
+  const KEYS = {
+    ArrowUp: 0, ArrowDown: 1, ArrowLeft: 2, ArrowRight: 3,
+    KeyZ: 4, KeyX: 5, Enter: 6, NumpadEnter: 6, ShiftLeft: 7, ShiftRight: 7,
+  };
+  let input = 0, mouseX = 0, mouseY = 0, mouseButtons = 0;
+  addEventListener("keydown", (e) => {
+    const bit = KEYS[e.code];
+    if (bit !== undefined) { input |= 1 << bit; e.preventDefault(); }
+  });
+  addEventListener("keyup", (e) => {
+    const bit = KEYS[e.code];
+    if (bit !== undefined) { input &= ~(1 << bit); e.preventDefault(); }
+  });
+  addEventListener("blur", () => { input = 0; mouseButtons = 0; });
+
+  const MOUSE_BITS = [0, 2, 1]; // DOM left, middle, right → bit 0 left, 1 right, 2 middle
+  canvas.addEventListener("mousemove", (e) => {
+    const r = canvas.getBoundingClientRect();
+    mouseX = Math.min(fbW - 1, Math.max(0, Math.floor((e.clientX - r.left) / r.width * fbW)));
+    mouseY = Math.min(fbH - 1, Math.max(0, Math.floor((e.clientY - r.top) / r.height * fbH)));
+  });
+  canvas.addEventListener("mousedown", (e) => { if (e.button < 3) mouseButtons |= 1 << MOUSE_BITS[e.button]; });
+  addEventListener("mouseup", (e) => { if (e.button < 3) mouseButtons &= ~(1 << MOUSE_BITS[e.button]); });
+  canvas.addEventListener("contextmenu", (e) => e.preventDefault());
+
+  // --- host imports (module "env") -------------------------------------------
+  const SAVE_MAX_BYTES = 64 * 1024;
+  const FB_MAX_SIDE = 1024;
+  const saveKey = "oxido-save:" + cfg.title;
+  const decoder = new TextDecoder();
+  const start = performance.now();
+  let memory = null, pendingSize = null, frameCount = 0n, uptime = 0;
+  const bytes = (ptr, len) => new Uint8Array(memory.buffer, ptr, len);
+
+  const env = {
+    oxido_log(ptr, len) { console.log("[game] " + decoder.decode(bytes(ptr, len))); },
+    oxido_save(ptr, len) {
+      if (len > SAVE_MAX_BYTES) return 0;
+      let s = "";
+      for (const b of bytes(ptr, len)) s += String.fromCharCode(b);
+      try { localStorage.setItem(saveKey, btoa(s)); return 1; } catch { return 0; }
+    },
+    oxido_load(ptr, maxLen) {
+      const s = localStorage.getItem(saveKey);
+      if (!s) return 0;
+      const data = Uint8Array.from(atob(s), (c) => c.charCodeAt(0));
+      const n = Math.min(data.length, maxLen);
+      bytes(ptr, n).set(data.subarray(0, n));
+      return n;
+    },
+    oxido_now_ms() { return performance.now() - start; },
+    oxido_frame_count() { return frameCount; },
+    oxido_uptime_ms() { return uptime; },
+    oxido_resize(w, h) {
+      if (w === 0 || h === 0 || w > FB_MAX_SIDE || h > FB_MAX_SIDE) return 0;
+      pendingSize = [w, h];
+      return 1;
+    },
+    oxido_mouse_pos() { return BigInt(mouseX) | (BigInt(mouseY) << 32n); },
+    oxido_mouse_buttons() { return mouseButtons; },
+    oxido_play_sfx() { return 0; }, // no audio in the browser yet
+    oxido_flash() {}, // cosmetic; not in the browser yet
+    oxido_asset_len() { return 0xFFFFFFFF; }, // no assets in the browser yet
+    oxido_asset_read() { return 0; },
+    oxido_beat_phase(bpm) { return bpm > 0 ? ((performance.now() - start) / 1000 * bpm / 60) % 1 : 0; },
+  };
+
+  function fail(msg) {
+    errorBox.textContent = "TRAP: " + msg;
+    errorBox.style.display = "block";
+  }
+
+  // --- boot --------------------------------------------------------------------
+  let game;
+  try {
+    const buf = await (await fetch(cfg.wasm)).arrayBuffer();
+    ({ instance: game } = await WebAssembly.instantiate(buf, { env }));
+  } catch (e) {
+    return fail("could not load " + cfg.wasm + ": " + e);
+  }
+  const g = game.exports;
+  memory = g.memory;
+  const missing = ["memory", "oxido_init", "oxido_update", "oxido_draw_ptr", "oxido_draw_len", "oxido_input_set"]
+    .filter((name) => !(name in g));
+  if (missing.length) return fail("missing exports: " + missing.join(", "));
+
+  canvas.focus();
+  const FRAME_MS = 1000 / 60;
+  let last = performance.now();
+  try { g.oxido_init(); } catch (e) { return fail(e); }
+
+  function frame(now) {
+    // ~60 Hz like the native loop, even on faster displays
+    if (now - last < FRAME_MS - 1) return requestAnimationFrame(frame);
+    const dt = now - last;
+    last = now;
+    uptime = now - start;
+    try {
+      g.oxido_input_set(input);
+      g.oxido_update(dt);
+      frameCount++;
+      if (g.oxido_should_exit && g.oxido_should_exit()) return; // game over: stop the loop
+      if (pendingSize) { setSize(pendingSize[0], pendingSize[1]); pendingSize = null; }
+      const ptr = g.oxido_draw_ptr() >>> 0;
+      const len = g.oxido_draw_len() >>> 0;
+      if (len === fbW * fbH * 4 && ptr + len <= memory.buffer.byteLength) {
+        image.data.set(bytes(ptr, len));
+        ctx.putImageData(image, 0, 0);
+      }
+    } catch (e) {
+      return fail(e);
+    }
+    requestAnimationFrame(frame);
+  }
+  requestAnimationFrame(frame);
+})();
@@ -0,0 +1,54 @@
+// Post-process pass: samples the native framebuffer texture directly into
+// `rect` (plain in mode 0), applying scanlines (mode 1) or scanlines +
+// curvature + vignette (mode 2).
+
+struct Locals {
+    rect: vec4<f32>,     // framebuffer rect in target pixels (x, y, w, h)
+    src_size: vec2<f32>, // framebuffer size in pixels
+    mode: u32,
+    _pad: u32,
+    background: vec4<f32>, // linear color outside the image
+}
+
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    // One full-screen triangle: (-1,-1), (3,-1), (-1,3)
+    let x = f32(i32(i & 1u) * 4 - 1);
+    let y = f32(i32(i >> 1u) * 4 - 1);
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    var uv = (pos.xy - r_locals.rect.xy) / r_locals.rect.zw;
+
+    if (r_locals.mode == 2u) {
+        // Barrel curvature
+        var c = uv * 2.0 - 1.0;
+        let bend = abs(c.yx) / vec2<f32>(6.0, 4.0);
+        c = c + c * bend * bend;
+        uv = c * 0.5 + 0.5;
+    }
+    if (uv.x < 0.0 || uv.y < 0.0 || uv.x > 1.0 || uv.y > 1.0) {
+        return r_locals.background;
+    }
+
+    var color = textureSampleLevel(r_tex_color, r_tex_sampler, uv, 0.0).rgb;
+
+    if (r_locals.mode >= 1u) {
+        // Darken the edges of each native row
+        let row = fract(uv.y * r_locals.src_size.y);
+        let scan = mix(0.65, 1.0, 0.5 + 0.5 * cos((row - 0.5) * 6.2831853));
+        color = color * scan;
+    }
+
+    if (r_locals.mode == 2u) {
+        let v = uv * (1.0 - uv.yx);
+        color = color * pow(clamp(v.x * v.y * 16.0, 0.0, 1.0), 0.15);
+    }
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,396 @@
+use pixels::{wgpu, Pixels, PixelsContext};
+use oxido_sdk::Palette;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// ===================== Post-process filters ===============================
+
+/// Presentation filter applied after the guest frame is uploaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    None,
+    /// Darkened lines between native rows.
+    Scanlines,
+    /// Scanlines + barrel curvature + vignette.
+    Crt,
+    /// CPU remap through a DMG-like LCD response (green tint, low contrast).
+    Dmg,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Filter::None),
+            "scanlines" => Ok(Filter::Scanlines),
+            "crt" => Ok(Filter::Crt),
+            "dmg" => Ok(Filter::Dmg),
+            other => Err(format!("unknown filter '{other}' (expected none|scanlines|crt|dmg)")),
+        }
+    }
+}
+
+impl Filter {
+    /// Shader mode for the GPU pass, or `None` when the default scaler is enough.
+    fn shader_mode(self) -> Option<u32> {
+        match self {
+            Filter::Scanlines => Some(1),
+            Filter::Crt => Some(2),
+            Filter::None | Filter::Dmg => None,
+        }
+    }
+}
+
+/// Accessible palette the runtime forces onto the final image (`--palette`,
+/// F4 cycles). Best effort: the guest still picks its own colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteOverride {
+    HighContrast,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl FromStr for PaletteOverride {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "high-contrast" => Ok(PaletteOverride::HighContrast),
+            "deuteranopia" => Ok(PaletteOverride::Deuteranopia),
+            "tritanopia" => Ok(PaletteOverride::Tritanopia),
+            other => Err(format!("unknown palette '{other}' (expected high-contrast|deuteranopia|tritanopia)")),
+        }
+    }
+}
+
+impl PaletteOverride {
+    pub fn palette(self) -> Palette {
+        match self {
+            PaletteOverride::HighContrast => Palette::HIGH_CONTRAST,
+            PaletteOverride::Deuteranopia => Palette::DEUTERANOPIA,
+            PaletteOverride::Tritanopia => Palette::TRITANOPIA,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteOverride::HighContrast => "high-contrast",
+            PaletteOverride::Deuteranopia => "deuteranopia",
+            PaletteOverride::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// F4 order: off → high-contrast → deuteranopia → tritanopia → off.
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(PaletteOverride::HighContrast),
+            Some(PaletteOverride::HighContrast) => Some(PaletteOverride::Deuteranopia),
+            Some(PaletteOverride::Deuteranopia) => Some(PaletteOverride::Tritanopia),
+            Some(PaletteOverride::Tritanopia) => None,
+        }
+    }
+}
+
+/// How the framebuffer is fitted into the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Largest integer multiple that fits, centered with bars.
+    #[default]
+    Integer,
+    /// Fill the whole window, ignoring aspect ratio.
+    Stretch,
+}
+
+impl FromStr for ScaleMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "integer" => Ok(ScaleMode::Integer),
+            "stretch" => Ok(ScaleMode::Stretch),
+            other => Err(format!("unknown scale mode '{other}' (expected integer|stretch)")),
+        }
+    }
+}
+
+/// Solid color around the game image (letterbox bars, `--bg` / manifest
+/// `bg`). Black by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Background(pub [u8; 3]);
+
+impl FromStr for Background {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("invalid color '{s}' (expected #RRGGBB, e.g. #1d2b53)"));
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+        Ok(Background([byte(0), byte(2), byte(4)]))
+    }
+}
+
+impl Background {
+    /// As a clear color for a surface of `format`. sRGB surfaces take linear
+    /// values, so the bars show exactly the given hex like the game's pixels do.
+    pub fn to_wgpu(self, format: wgpu::TextureFormat) -> wgpu::Color {
+        let [r, g, b] = self.0.map(|c| {
+            let c = c as f64 / 255.0;
+            if !format.is_srgb() { c } else if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        });
+        wgpu::Color { r, g, b, a: 1.0 }
+    }
+}
+
+/// GPU pass that replaces the default scaling renderer. It samples the native
+/// framebuffer texture directly, so the effect runs at window resolution while
+/// the framebuffer itself stays untouched.
+pub struct PostProcess {
+    mode: u32,
+    scale_mode: ScaleMode,
+    src_size: (u32, u32),
+    surface_size: (u32, u32),
+    uniform_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    render_pipeline: wgpu::RenderPipeline,
+    /// Clear color, also drawn by the shader outside the image.
+    background: wgpu::Color,
+}
+
+const LOCALS_SIZE: u64 = 48; // vec4 rect + vec2 src_size + u32 mode + pad + vec4 background
+
+impl PostProcess {
+    /// Returns `None` when the default integer scaler already does the job.
+    pub fn new(pixels: &Pixels, filter: Filter, scale_mode: ScaleMode, surface_size: (u32, u32)) -> Option<Self> {
+        let mode = match (filter.shader_mode(), scale_mode) {
+            (Some(mode), _) => mode,
+            (None, ScaleMode::Stretch) => 0,
+            (None, ScaleMode::Integer) => return None,
+        };
+        let device = pixels.device();
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/post.wgsl"));
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("oxido_post_sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("oxido_post_locals"),
+            size: LOCALS_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("oxido_post_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(LOCALS_SIZE),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("oxido_post_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("oxido_post_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState { module: &module, entry_point: "vs_main", buffers: &[] },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: pixels.render_texture_format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let bind_group = make_bind_group(pixels.context(), &bind_group_layout, &sampler, &uniform_buffer);
+        let extent = pixels.context().texture_extent;
+        Some(Self {
+            mode,
+            scale_mode,
+            src_size: (extent.width, extent.height),
+            surface_size,
+            uniform_buffer,
+            bind_group_layout,
+            bind_group,
+            sampler,
+            render_pipeline,
+            background: wgpu::Color::BLACK,
+        })
+    }
+
+    /// Same as `Pixels::clear_color`, for when this pass does the drawing.
+    pub fn set_background(&mut self, color: wgpu::Color) {
+        self.background = color;
+    }
+
+    /// Call after `Pixels::resize_buffer`, which recreates the source texture.
+    pub fn rebind(&mut self, pixels: &Pixels) {
+        let ctx = pixels.context();
+        self.bind_group = make_bind_group(ctx, &self.bind_group_layout, &self.sampler, &self.uniform_buffer);
+        self.src_size = (ctx.texture_extent.width, ctx.texture_extent.height);
+    }
+
+    /// Keep in sync with `Pixels::resize_surface` (needed for stretch mode).
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.surface_size = (width, height);
+    }
+
+    /// Draws the filtered frame into `target`: inside the scaler's letterbox
+    /// rect for integer scaling, or over the whole surface when stretching.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, ctx: &PixelsContext) {
+        let (x, y, w, h) = match self.scale_mode {
+            ScaleMode::Integer => ctx.scaling_renderer.clip_rect(),
+            ScaleMode::Stretch => (0, 0, self.surface_size.0, self.surface_size.1),
+        };
+        let mut locals = [0u8; LOCALS_SIZE as usize];
+        let fields = [x as f32, y as f32, w as f32, h as f32, self.src_size.0 as f32, self.src_size.1 as f32];
+        for (i, v) in fields.iter().enumerate() {
+            locals[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        locals[24..28].copy_from_slice(&self.mode.to_le_bytes());
+        let bg = self.background;
+        for (i, v) in [bg.r, bg.g, bg.b, bg.a].iter().enumerate() {
+            locals[32 + i * 4..36 + i * 4].copy_from_slice(&(*v as f32).to_le_bytes());
+        }
+        ctx.queue.write_buffer(&self.uniform_buffer, 0, &locals);
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("oxido_post_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(self.background), store: true },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+fn make_bind_group(
+    ctx: &PixelsContext,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    uniforms: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    let view = ctx.texture.create_view(&wgpu::TextureViewDescriptor::default());
+    ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("oxido_post_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 2, resource: uniforms.as_entire_binding() },
+        ],
+    })
+}
+
+// ===================== DMG LCD response (CPU) =============================
+
+/// Darkest and lightest shades the DMG panel can show.
+const DMG_DARK: [f32; 3] = [24.0, 44.0, 20.0];
+const DMG_LIGHT: [f32; 3] = [170.0, 190.0, 80.0];
+const DMG_GAMMA: f32 = 1.25;
+
+/// Luma (0..=255) → LCD color. Built once; per pixel it's a weighted sum and a lookup.
+fn dmg_lut() -> &'static [[u8; 3]; 256] {
+    static LUT: OnceLock<[[u8; 3]; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = [[0u8; 3]; 256];
+        for (l, out) in lut.iter_mut().enumerate() {
+            let t = (l as f32 / 255.0).powf(DMG_GAMMA);
+            for c in 0..3 {
+                out[c] = (DMG_DARK[c] + (DMG_LIGHT[c] - DMG_DARK[c]) * t).round() as u8;
+            }
+        }
+        lut
+    })
+}
+
+/// Remaps an RGBA8 frame in place through the DMG LCD response. Alpha is kept.
+pub fn apply_dmg(frame: &mut [u8]) {
+    let lut = dmg_lut();
+    for px in frame.chunks_exact_mut(4) {
+        // Rec. 601 luma in fixed point (weights sum to 256)
+        let l = (px[0] as u32 * 77 + px[1] as u32 * 150 + px[2] as u32 * 29) >> 8;
+        px[..3].copy_from_slice(&lut[l as usize]);
+    }
+}
+
+fn luma(px: &[u8]) -> u32 {
+    (px[0] as u32 * 77 + px[1] as u32 * 150 + px[2] as u32 * 29) >> 8
+}
+
+/// Remaps an RGBA8 frame onto the four colors of `pal` (dark → light) by
+/// brightness. A frame with at most four distinct colors (a 4-shade cart)
+/// maps its colors in brightness order, spread over the palette. Anything
+/// else is cut into four brightness bands between its darkest and lightest
+/// pixel. Colors of equal brightness merge either way. Alpha is kept.
+pub fn remap_palette(frame: &mut [u8], pal: &Palette) {
+    let target = pal.0.map(|c| c.to_le_bytes());
+    let mut distinct: Vec<[u8; 3]> = Vec::with_capacity(4);
+    let (mut lo, mut hi) = (255, 0);
+    for px in frame.chunks_exact(4) {
+        let l = luma(px);
+        (lo, hi) = (lo.min(l), hi.max(l));
+        let rgb = [px[0], px[1], px[2]];
+        if distinct.len() <= 4 && !distinct.contains(&rgb) { distinct.push(rgb); }
+    }
+
+    if distinct.len() <= 4 {
+        distinct.sort_by_key(|c| luma(c));
+        let n = distinct.len();
+        // a single color (e.g. a cleared screen) keeps its own brightness
+        let slot = |i: usize| if n > 1 { (i * 3 + (n - 1) / 2) / (n - 1) } else { (lo * 4 / 256) as usize };
+        for px in frame.chunks_exact_mut(4) {
+            let i = distinct.iter().position(|c| c[..] == px[..3]).unwrap_or(0);
+            px[..3].copy_from_slice(&target[slot(i)][..3]);
+        }
+    } else {
+        let span = hi - lo + 1;
+        for px in frame.chunks_exact_mut(4) {
+            let band = ((luma(px) - lo) * 4 / span).min(3) as usize;
+            px[..3].copy_from_slice(&target[band][..3]);
+        }
+    }
+}
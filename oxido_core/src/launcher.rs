@@ -0,0 +1,150 @@
+//! Built-in cart picker for `oxido run <DIR>` on a folder of carts: a list
+//! drawn with the SDK's `Menu`, each choice started as a child process.
+//! The menu window hides while a game runs and comes back when it exits.
+
+use crate::runtime::input_bit;
+use anyhow::*;
+use oxido_sdk::{Frame, InputState, Menu, Palette};
+use pixels::{Pixels, SurfaceTexture};
+use std::{
+    path::PathBuf,
+    process::Child,
+    time::{Duration, Instant},
+};
+use winit::{
+    dpi::LogicalSize,
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+const W: u32 = 160;
+const H: u32 = 144;
+const SCALE: u32 = 3;
+/// Menu rows that fit between the header and the footer.
+const ROWS: usize = 11;
+
+/// One playable cart in the list.
+pub struct LauncherEntry {
+    pub title: String,
+    pub path: PathBuf,
+}
+
+/// Shows the picker until the window is closed (or Esc). `notes` are shown
+/// under the list (e.g. carts that were skipped). `launch` starts a cart and
+/// returns its process; the menu waits for it before taking input again.
+pub fn run_launcher(
+    entries: Vec<LauncherEntry>,
+    notes: Vec<String>,
+    mut launch: impl FnMut(&LauncherEntry) -> Result<Child> + 'static,
+) -> Result<()> {
+    const FRAME_TIME: Duration = Duration::from_micros(16_667);
+    ensure!(!entries.is_empty(), "no carts to choose from");
+
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title("OxidoBoy")
+        .with_inner_size(LogicalSize::new((W * SCALE) as f64, (H * SCALE) as f64))
+        .with_min_inner_size(LogicalSize::new(W as f64, H as f64))
+        .build(&event_loop)?;
+    let size = window.inner_size();
+    let mut pixels = Pixels::new(W, H, SurfaceTexture::new(size.width, size.height, &window))?;
+
+    let mut menu = Menu::new(entries.iter().map(|e| e.title.clone()));
+    let mut input = InputState::new();
+    let mut input_bits = 0u32;
+    let mut top = 0usize;
+    let mut status = notes.join(" ");
+    let mut child: Option<(Child, usize)> = None;
+    let mut last = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(s) => { let _ = pixels.resize_surface(s.width, s.height); }
+                WindowEvent::KeyboardInput { input: key, .. } => {
+                    let pressed = key.state == ElementState::Pressed;
+                    if pressed && key.virtual_keycode == Some(VirtualKeyCode::Escape) && child.is_none() {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    let bit = input_bit(key.virtual_keycode);
+                    if pressed { input_bits |= bit; } else { input_bits &= !bit; }
+                }
+                WindowEvent::Focused(false) => input_bits = 0,
+                _ => {}
+            },
+
+            Event::MainEventsCleared => {
+                // a game is running: just wait for it
+                if let Some((proc, idx)) = &mut child {
+                    match proc.try_wait() {
+                        std::result::Result::Ok(None) => {}
+                        done => {
+                            let title = &entries[*idx].title;
+                            status = match done {
+                                std::result::Result::Ok(Some(code)) if code.success() => String::new(),
+                                std::result::Result::Ok(Some(_)) => format!("{title} exited with an error"),
+                                _ => format!("lost track of {title}"),
+                            };
+                            child = None;
+                            input_bits = 0;
+                            window.set_visible(true);
+                            window.focus_window();
+                        }
+                    }
+                    *control_flow = ControlFlow::WaitUntil(Instant::now() + Duration::from_millis(100));
+                    return;
+                }
+
+                if last.elapsed() >= FRAME_TIME {
+                    last = Instant::now();
+                    input.update(input_bits);
+                    if let Some(i) = menu.handle_input(&input) {
+                        match launch(&entries[i]) {
+                            std::result::Result::Ok(proc) => {
+                                child = Some((proc, i));
+                                window.set_visible(false);
+                            }
+                            Err(e) => status = format!("{}: {e}", entries[i].title),
+                        }
+                        input_bits = 0;
+                    }
+                    // keep the cursor inside the visible rows
+                    if menu.selected < top { top = menu.selected; }
+                    if menu.selected >= top + ROWS { top = menu.selected + 1 - ROWS; }
+                    draw(pixels.frame_mut(), &menu, top, &status);
+                    if pixels.render().is_err() { *control_flow = ControlFlow::Exit; }
+                }
+                if *control_flow != ControlFlow::Exit {
+                    *control_flow = ControlFlow::WaitUntil(last + FRAME_TIME);
+                }
+            }
+            _ => {}
+        }
+    });
+}
+
+fn draw(frame: &mut [u8], menu: &Menu, top: usize, status: &str) {
+    let pal = Palette::GB;
+    let cols = (W as usize - 8) / 6;
+    let mut f = Frame { data: frame, w: W as usize, h: H as usize };
+    f.clear(pal.color(0));
+    f.text5x7(4, 4, "OXIDOBOY", pal.color(3));
+    let count = format!("{}/{}", menu.selected + 1, menu.items.len());
+    f.text5x7(W as i32 - 4 - 6 * count.len() as i32, 4, &count, pal.color(2));
+    f.rect(4, 13, W as i32 - 8, 1, pal.color(1));
+
+    // only the rows in view, names cut to the screen width
+    let view = Menu {
+        items: menu.items.iter().skip(top).take(ROWS).map(|s| s.chars().take(cols - 1).collect()).collect(),
+        selected: menu.selected - top,
+    };
+    view.draw(&mut f, 4, 17, &pal, pal.color(3));
+
+    f.rect(4, H as i32 - 22, W as i32 - 8, 1, pal.color(1));
+    let chars: Vec<char> = status.chars().collect();
+    for (i, part) in chars.chunks(cols).take(2).enumerate() {
+        f.text5x7(4, H as i32 - 19 + i as i32 * 9, &part.iter().collect::<String>(), pal.color(2));
+    }
+}
@@ -1 +1,5 @@
+pub mod filter;
+pub mod launcher;
 pub mod runtime;
+mod record;
+pub mod wav;
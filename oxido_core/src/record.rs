@@ -0,0 +1,110 @@
+//! `--record-audio`: the audio callback pushes its output into a lock-free
+//! ring and a writer thread drains it into a WAV file.
+
+use crate::wav::WavWriter;
+use anyhow::*;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Single-producer / single-consumer ring of f32 samples (stored as bits).
+/// The producer never blocks or allocates: when the ring is full the rest
+/// of the block is dropped and counted.
+pub(crate) struct SampleRing {
+    buf: Box<[AtomicU32]>,
+    head: AtomicUsize, // next write (producer)
+    tail: AtomicUsize, // next read (consumer)
+    dropped: AtomicU64,
+}
+
+impl SampleRing {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            buf: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Audio thread side.
+    pub(crate) fn push(&self, samples: &[f32]) {
+        let cap = self.buf.len();
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        let free = cap - (head - tail);
+        let n = samples.len().min(free);
+        for (i, s) in samples[..n].iter().enumerate() {
+            self.buf[(head + i) % cap].store(s.to_bits(), Ordering::Relaxed);
+        }
+        self.head.store(head + n, Ordering::Release);
+        if n < samples.len() {
+            self.dropped.fetch_add((samples.len() - n) as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Writer thread side: moves everything available into `out`.
+    fn drain_into(&self, out: &mut Vec<f32>) {
+        let cap = self.buf.len();
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        out.extend((tail..head).map(|i| f32::from_bits(self.buf[i % cap].load(Ordering::Relaxed))));
+        self.tail.store(head, Ordering::Release);
+    }
+}
+
+/// ~2.7 s of stereo at 48 kHz: plenty of slack for a slow disk.
+pub(crate) const RING_SAMPLES: usize = 1 << 18;
+
+/// Owns the writer thread; `finish` flushes and closes the file.
+pub(crate) struct Recorder {
+    path: PathBuf,
+    ring: Arc<SampleRing>,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Result<u64>>,
+}
+
+impl Recorder {
+    /// Creates `path` (stereo 32-bit float at `sample_rate`) and starts draining `ring`.
+    pub(crate) fn start(path: PathBuf, ring: Arc<SampleRing>, sample_rate: u32) -> Result<Self> {
+        let mut writer = WavWriter::create(&path, sample_rate, 2)
+            .with_context(|| format!("could not create {}", path.display()))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let (r, s) = (ring.clone(), stop.clone());
+        let handle = thread::spawn(move || -> Result<u64> {
+            let mut chunk = Vec::new();
+            loop {
+                let last = s.load(Ordering::Acquire);
+                r.drain_into(&mut chunk);
+                writer.write(&chunk)?;
+                chunk.clear();
+                if last { break; }
+                thread::sleep(Duration::from_millis(20));
+            }
+            writer.finish()
+        });
+        Ok(Self { path, ring, stop, handle })
+    }
+
+    /// Stops the writer, patches the WAV header and reports what was saved.
+    pub(crate) fn finish(self) {
+        self.stop.store(true, Ordering::Release);
+        match self.handle.join() {
+            std::result::Result::Ok(std::result::Result::Ok(frames)) => {
+                eprintln!("🎙️  OxidoBoy: recorded {frames} frames to {}", self.path.display());
+                let dropped = self.ring.dropped.load(Ordering::Relaxed);
+                if dropped > 0 {
+                    eprintln!("⚠️  OxidoBoy: {dropped} samples were dropped (the writer fell behind)");
+                }
+            }
+            std::result::Result::Ok(Err(e)) => eprintln!("⚠️  OxidoBoy: audio recording failed: {e:#}"),
+            Err(_) => eprintln!("⚠️  OxidoBoy: audio recording thread panicked"),
+        }
+    }
+}
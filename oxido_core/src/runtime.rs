@@ -1,3 +1,5 @@
+use crate::filter::{apply_dmg, remap_palette, Background, Filter, PaletteOverride, PostProcess, ScaleMode};
+use crate::record::{Recorder, SampleRing, RING_SAMPLES};
 use anyhow::*;
 use pixels::{Pixels, SurfaceTexture};
 use wasmtime::*;
@@ -5,21 +7,32 @@ use winit::{
     dpi::LogicalSize,
     event::*,
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{WindowBuilder, WindowLevel},
 };
 use winit::event::{ElementState, VirtualKeyCode};
 use std::{
+    collections::{HashMap, VecDeque},
     fs,
-    sync::{Arc, Mutex},
-    time::{Duration, Instant, SystemTime},
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 // ===================== Audio (host) ===============================
 
+/// Shape of the attack/decay/release segments. Either way the ms
+/// parameters are the time to cross the full span (0→1, 1→S, 1→0).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum EnvCurve {
+    #[default]
+    Linear,
+    /// Fast start that eases into the target, like an analog RC envelope.
+    Exp,
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 struct HostCh {
     // Parameters received from the game
-    kind: u32,          // 0=pulse, 1=pulse, 2=noise
+    kind: u32,          // 0=pulse, 1=pulse, 2=noise, 7=wavetable
     base_freq: f32,     // Hz
     vol: f32,           // 0..1 (base gain)
     duty: f32,          // 0..1 (pulse)
@@ -31,6 +44,10 @@ struct HostCh {
     // arpeggio (semitones relative) and rate in Hz
     arp_a: i32, arp_b: i32, arp_c: i32, arp_rate_hz: f32,
 
+    env_curve: EnvCurve,
+    portamento_ms: f32, // glide time between notes (0 = jump)
+    noise_width: u32,   // LFSR bits: 7 (short, metallic), anything else 15 (hiss)
+
     // runtime state
     phase: f32,         // 0..1 (pulse)
     noise: u32,         // LFSR
@@ -38,33 +55,281 @@ struct HostCh {
     env_state: u32,     // 0=idle,1=A,2=D,3=S,4=R
     gate_prev: bool,
     arp_phase: f32,     // 0..1 (0..1 → A→B→C)
+    out_gain: f32,      // vol*env after the de-click slew
+    vol_smooth: f32,    // vol / duty after the one-pole smoothing
+    duty_smooth: f32,
+    glide_freq: f32,    // Hz actually playing (trails base_freq while gliding)
+    glide_target: f32,  // base_freq the current glide heads to
+    glide_rate: f32,    // Hz per second
 }
 
 #[derive(Clone, Copy, Default)]
 struct WireCh {
-    // exact layout sent by the game (13 * 4 bytes)
+    // exact layout sent by the game (WIRE_FIELDS * 4 bytes)
     kind: u32, base_freq: f32, vol: f32, duty: f32, gate: u32,
     a_ms: f32, d_ms: f32, s_lvl: f32, r_ms: f32,
     arp_a: i32, arp_b: i32, arp_c: i32, arp_rate_hz: f32,
+    // optional trailing fields: carts built before they existed send fewer
+    // and get the defaults (0)
+    env_curve: u32, // 0=linear, 1=exp
+    portamento_ms: f32,
+    noise_width: u32, // 15 or 7 (0 = 15)
+}
+
+/// Fields per channel every cart sends (the original layout).
+const WIRE_FIELDS_MIN: usize = 13;
+/// Fields per channel the host understands.
+const WIRE_FIELDS: usize = 16;
+
+/// Most one-shot samples mixed at once; a new one past this drops the oldest.
+const SFX_VOICES: usize = 4;
+
+/// Decoded SFX, already at the output rate.
+type SfxData = Arc<Vec<[f32; 2]>>;
+
+/// A sample playing on one of the SFX voices.
+struct SfxVoice {
+    data: SfxData,
+    pos: usize,
+    seq: u64, // start order (from 1), to find the oldest voice
+}
+
+/// Longest wavetable a cart may send (samples).
+const WAVE_MAX: usize = 256;
+
+/// One period of the waveform played by kind 7 channels (shared by all of
+/// them, like the Game Boy's wave RAM).
+#[derive(Clone, Copy)]
+struct Wavetable {
+    samples: [f32; WAVE_MAX],
+    len: usize,
+}
+
+impl Default for Wavetable {
+    /// 32-sample sine, used until the cart sends its own table.
+    fn default() -> Self {
+        let mut samples = [0.0; WAVE_MAX];
+        for (i, s) in samples.iter_mut().take(32).enumerate() {
+            *s = (i as f32 / 32.0 * std::f32::consts::TAU).sin();
+        }
+        Self { samples, len: 32 }
+    }
+}
+
+impl Wavetable {
+    /// Value at `phase` (0..1), linearly interpolated and wrapping at the end.
+    fn sample(&self, phase: f32) -> f32 {
+        let pos = phase * self.len as f32;
+        let i0 = (pos as usize).min(self.len - 1);
+        let i1 = (i0 + 1) % self.len;
+        let t = pos - i0 as f32;
+        self.samples[i0] + (self.samples[i1] - self.samples[i0]) * t
+    }
+}
+
+/// Global echo over the final mix. `buf` holds exactly one delay time of
+/// output frames; it's allocated and freed by `set_delay` on the game thread,
+/// never in the audio callback. Empty = off.
+#[derive(Default)]
+struct Delay {
+    buf: Vec<[f32; 2]>,
+    pos: usize,
+    feedback: f32,
+    mix: f32,
+}
+
+impl Delay {
+    #[inline]
+    fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let len = self.buf.len();
+        let Some(slot) = self.buf.get_mut(self.pos) else { return (l, r) };
+        let [dl, dr] = *slot;
+        *slot = [l + dl * self.feedback, r + dr * self.feedback];
+        self.pos = (self.pos + 1) % len;
+        (l + dl * self.mix, r + dr * self.mix)
+    }
+}
+
+/// One-pole low-pass over the final mix (6 dB/octave, like a small
+/// speaker rolling off the top end). `coef` 0 = off: samples pass through
+/// untouched.
+#[derive(Clone, Copy, Default)]
+struct LowPass {
+    coef: f32,
+    y: [f32; 2],
+}
+
+impl LowPass {
+    #[inline]
+    fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.coef == 0.0 { return (l, r); }
+        self.y[0] += (l - self.y[0]) * self.coef;
+        self.y[1] += (r - self.y[1]) * self.coef;
+        (self.y[0], self.y[1])
+    }
+}
+
+/// Bitcrusher over the final mix: each sample snaps to steps of
+/// 1/2^(`bits`-1), like a `bits`-bit DAC, and is held for `rate_div`
+/// output frames. `bits` 0 = full
+/// resolution; `rate_div` 0 or 1 = every frame.
+#[derive(Clone, Copy, Default)]
+struct Crusher {
+    bits: u32,
+    rate_div: u32,
+    held: [f32; 2],
+    count: u32,
+}
+
+impl Crusher {
+    #[inline]
+    fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.bits == 0 && self.rate_div <= 1 { return (l, r); }
+        if self.count == 0 {
+            self.held = if self.bits == 0 {
+                [l, r]
+            } else {
+                let levels = (1u32 << (self.bits - 1)) as f32;
+                [(l * levels).round() / levels, (r * levels).round() / levels]
+            };
+        }
+        self.count = (self.count + 1) % self.rate_div.max(1);
+        (self.held[0], self.held[1])
+    }
+}
+
+/// Audible range accepted for the low-pass cutoff (Hz).
+pub const LOWPASS_RANGE: std::ops::RangeInclusive<f32> = 20.0..=20_000.0;
+
+/// Mixer state shared between the frame loop and the audio callback.
+#[derive(Default)]
+struct AudioShared {
+    chans: [HostCh; 4],
+    // fixed slots, so neither the callback nor `oxido_play_sfx` allocates
+    // or shifts voices while holding the lock
+    sfx: [Option<SfxVoice>; SFX_VOICES],
+    sfx_seq: u64,
+    wave: Wavetable,
+    // the callback takes `delay` out while mixing; new settings wait in
+    // `new_delay` so they can't be overwritten when it's handed back. The
+    // buffer it replaces waits in `old_delay` for the next `set_delay` to
+    // free (that also fills `new_delay`, so at most one ever waits there)
+    delay: Delay,
+    new_delay: Option<Delay>,
+    old_delay: Option<Delay>,
+    lowpass: LowPass,
+    crush: Crusher,
+    // master mute (window in the background) and the callback's gain ramp
+    // toward it
+    master_mute: bool,
+    master_gain: f32,
+    // audio clock: the last callback started playing frame `clock_frames`
+    // (counted from stream start) at `clock_at`, and handed out `clock_buf`
+    clock_frames: u64,
+    clock_buf: u64,
+    clock_at: Option<Instant>,
+    // debugging aids: any solo silences every channel that isn't soloed
+    mute: [bool; 4],
+    solo: [bool; 4],
+}
+
+impl AudioShared {
+    fn audible(&self, ch: usize) -> bool {
+        if self.solo.iter().any(|&s| s) { self.solo[ch] } else { !self.mute[ch] }
+    }
+
+    /// Puts `voice` on a free SFX slot, or in place of the oldest voice if
+    /// `voice` started after it (otherwise `voice` is dropped).
+    fn add_sfx(&mut self, voice: SfxVoice) {
+        // free slots sort first: seqs start at 1
+        let age = |s: &Option<SfxVoice>| s.as_ref().map_or(0, |v| v.seq);
+        let Some(slot) = self.sfx.iter_mut().min_by_key(|s| age(s)) else { return };
+        if age(slot) < voice.seq { *slot = Some(voice); }
+    }
+}
+
+/// What `oxido_play_sfx` needs: the cart's sounds and the mixer to start them on.
+#[derive(Clone)]
+struct SfxBank {
+    sounds: Arc<Vec<SfxData>>,
+    mixer: Arc<Mutex<AudioShared>>,
+}
+
+/// Time as heard from the speakers, for `oxido_beat_phase`.
+#[derive(Clone)]
+struct AudioClock {
+    shared: Arc<Mutex<AudioShared>>,
+    sample_rate: f32,
+}
+
+impl AudioClock {
+    /// Seconds of audio played so far. Between callbacks the position is
+    /// advanced by wall time, but never past the end of the last buffer, so
+    /// it is smooth and never runs backwards. `None` before the first callback.
+    fn secs(&self) -> Option<f64> {
+        let shared = self.shared.lock().ok()?;
+        let at = shared.clock_at?;
+        let ahead = (at.elapsed().as_secs_f64() * self.sample_rate as f64).min(shared.clock_buf as f64);
+        Some((shared.clock_frames as f64 + ahead) / self.sample_rate as f64)
+    }
 }
 
 struct AudioEngine {
-    channels: Arc<Mutex<[HostCh; 4]>>,
+    shared: Arc<Mutex<AudioShared>>,
     _stream: cpal::Stream,
     sample_rate: f32,
 }
 
+/// Prints output device names for `--audio-device`.
+fn list_output_devices(host: &cpal::Host) {
+    use cpal::traits::{DeviceTrait, HostTrait};
+    let names: Vec<String> = host.output_devices()
+        .map(|it| it.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default();
+    if names.is_empty() {
+        eprintln!("   (no output devices found)");
+    } else {
+        eprintln!("   available output devices:");
+        for n in names { eprintln!("     {n}"); }
+    }
+}
+
 impl AudioEngine {
-    fn new() -> Option<Self> {
+    /// Opens `device_name` (default output if `None` or not found) at
+    /// `sample_rate` (device default if `None` or unsupported).
+    /// `record` receives a copy of everything played (`--record-audio`).
+    fn with_config(device_name: Option<&str>, sample_rate: Option<u32>, record: Option<Arc<SampleRing>>) -> Option<Self> {
         use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
         let host = cpal::default_host();
-        let device = host.default_output_device()?;
-        let cfg = device.default_output_config().ok()?;
+        let named = device_name.and_then(|name| {
+            let found = host.output_devices().ok()?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false));
+            if found.is_none() {
+                eprintln!("⚠️  OxidoBoy: audio device '{name}' not found; using the default");
+                list_output_devices(&host);
+            }
+            found
+        });
+        let Some(device) = named.or_else(|| host.default_output_device()) else {
+            eprintln!("⚠️  OxidoBoy: no audio output device; running muted");
+            return None;
+        };
+        let wanted = sample_rate.and_then(|rate| {
+            let cfg = device.supported_output_configs().ok()?
+                .find(|c| c.min_sample_rate().0 <= rate && rate <= c.max_sample_rate().0)
+                .map(|c| c.with_sample_rate(cpal::SampleRate(rate)));
+            if cfg.is_none() {
+                eprintln!("⚠️  OxidoBoy: {rate} Hz not supported by this device; using its default rate");
+            }
+            cfg
+        });
+        let cfg = wanted.or_else(|| device.default_output_config().ok())?;
         let sample_rate = cfg.sample_rate().0 as f32;
 
-        let channels = Arc::new(Mutex::new([HostCh::default(); 4]));
+        let shared = Arc::new(Mutex::new(AudioShared::default()));
 
-        let chs = channels.clone();
+        let chs = shared.clone();
+        let rec = record;
         let build = |sf| -> Result<cpal::Stream> {
             let config = cpal::StreamConfig {
                 channels: 2,
@@ -77,7 +342,10 @@ impl AudioEngine {
                     let mut t = 0usize;
                     Ok(device.build_output_stream(
                         &config,
-                        move |out: &mut [f32], _| fill_buffer(out, sample_rate, &chs, &mut t),
+                        move |out: &mut [f32], _| {
+                            fill_buffer(out, sample_rate, &chs, &mut t);
+                            if let Some(r) = &rec { r.push(out); }
+                        },
                         move |e| eprintln!("audio error: {e}"),
                         None,
                     )?)
@@ -89,6 +357,7 @@ impl AudioEngine {
                         move |out: &mut [i16], _| {
                             let mut buf = vec![0.0f32; out.len()];
                             fill_buffer(&mut buf, sample_rate, &chs, &mut t);
+                            if let Some(r) = &rec { r.push(&buf); }
                             for (i, s) in buf.iter().enumerate() {
                                 out[i] = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
                             }
@@ -104,6 +373,7 @@ impl AudioEngine {
                         move |out: &mut [u16], _| {
                             let mut buf = vec![0.0f32; out.len()];
                             fill_buffer(&mut buf, sample_rate, &chs, &mut t);
+                            if let Some(r) = &rec { r.push(&buf); }
                             for (i, s) in buf.iter().enumerate() {
                                 out[i] = (((s.clamp(-1.0, 1.0) * 0.5) + 0.5) * u16::MAX as f32) as u16;
                             }
@@ -116,13 +386,100 @@ impl AudioEngine {
             }
         };
 
-        let stream = build(cfg.sample_format()).ok()?;
+        let stream = match build(cfg.sample_format()) {
+            std::result::Result::Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("⚠️  OxidoBoy: could not open the audio stream ({e}); running muted");
+                list_output_devices(&host);
+                return None;
+            }
+        };
         stream.play().ok()?;
-        Some(Self { channels, _stream: stream, sample_rate })
+        Some(Self { shared, _stream: stream, sample_rate })
+    }
+
+    fn clock(&self) -> AudioClock {
+        AudioClock { shared: self.shared.clone(), sample_rate: self.sample_rate }
+    }
+
+    /// Copy of the channels as the mixer last left them (for the F3 meters).
+    fn snapshot(&self) -> [HostCh; 4] {
+        self.shared.lock().map(|s| s.chans).unwrap_or_default()
+    }
+
+    /// Silences channel `ch` (0..4) without touching what the game sends.
+    fn set_mute(&self, ch: usize, on: bool) {
+        if let (std::result::Result::Ok(mut shared), true) = (self.shared.lock(), ch < 4) { shared.mute[ch] = on; }
+    }
+
+    /// While any channel is soloed, only soloed channels are heard.
+    fn set_solo(&self, ch: usize, on: bool) {
+        if let (std::result::Result::Ok(mut shared), true) = (self.shared.lock(), ch < 4) { shared.solo[ch] = on; }
+    }
+
+    /// Fades the whole output out (or back in) over DECLICK_MS; channels
+    /// and SFX keep running underneath.
+    fn set_master_mute(&self, on: bool) {
+        if let std::result::Result::Ok(mut shared) = self.shared.lock() { shared.master_mute = on; }
+    }
+
+    /// Replaces the wavetable with `samples` (clamped to -1..=1). Tables
+    /// outside 2..=WAVE_MAX samples are ignored.
+    fn set_wave(&self, samples: impl ExactSizeIterator<Item = f32>) {
+        let len = samples.len();
+        if !(2..=WAVE_MAX).contains(&len) { return; }
+        if let std::result::Result::Ok(mut shared) = self.shared.lock() {
+            for (dst, s) in shared.wave.samples.iter_mut().zip(samples) {
+                *dst = if s.is_nan() { 0.0 } else { s.clamp(-1.0, 1.0) };
+            }
+            shared.wave.len = len;
+        }
+    }
+
+    /// Echo: repeats the output every `time_ms` (1..=1000), each repeat
+    /// scaled by `feedback` (0..=0.95, so it always dies out), added to the
+    /// dry signal at `mix` (0..=1). `time_ms` 0 turns it off.
+    fn set_delay(&self, time_ms: f32, feedback: f32, mix: f32) {
+        let frames = (time_ms.clamp(0.0, 1000.0) / 1000.0 * self.sample_rate) as usize;
+        let delay = Delay {
+            buf: vec![[0.0; 2]; frames],
+            pos: 0,
+            feedback: feedback.clamp(0.0, 0.95),
+            mix: mix.clamp(0.0, 1.0),
+        };
+        // a pending setting never picked up and the buffer the callback
+        // retired are dropped at the end of this call, after the lock
+        let _freed = match self.shared.lock() {
+            std::result::Result::Ok(mut shared) => (shared.new_delay.replace(delay), shared.old_delay.take()),
+            Err(_) => (None, None),
+        };
+    }
+
+    /// Low-pass cutoff in Hz; `None` (or a cutoff at or above Nyquist)
+    /// bypasses the filter.
+    fn set_lowpass(&self, hz: Option<f32>) {
+        let coef = match hz {
+            Some(hz) if hz > 0.0 && hz < self.sample_rate / 2.0 => 1.0 - (-std::f32::consts::TAU * hz / self.sample_rate).exp(),
+            _ => 0.0,
+        };
+        if let std::result::Result::Ok(mut shared) = self.shared.lock() {
+            shared.lowpass.coef = coef;
+            if coef == 0.0 { shared.lowpass.y = [0.0; 2]; }
+        }
+    }
+
+    /// Bitcrusher: `bits` 1..=16 quantizes the mix (0 = off), `rate_div`
+    /// holds each sample for that many frames (1 = off), e.g. 4 at 48 kHz
+    /// sounds like 12 kHz.
+    fn set_crush(&self, bits: u32, rate_div: u32) {
+        if let std::result::Result::Ok(mut shared) = self.shared.lock() {
+            shared.crush = Crusher { bits: bits.min(16), rate_div: rate_div.clamp(1, 64), ..Crusher::default() };
+        }
     }
 
     fn set_params(&self, src: &[WireCh]) {
-        if let std::result::Result::Ok(mut dst) = self.channels.lock() {
+        if let std::result::Result::Ok(mut shared) = self.shared.lock() {
+            let dst = &mut shared.chans;
             for i in 0..dst.len().min(src.len()) {
                 let prev = dst[i];
                 let s = src[i];
@@ -145,6 +502,10 @@ impl AudioEngine {
                 h.arp_c = s.arp_c;
                 h.arp_rate_hz = s.arp_rate_hz.max(0.0);
 
+                h.env_curve = if s.env_curve == 1 { EnvCurve::Exp } else { EnvCurve::Linear };
+                h.portamento_ms = s.portamento_ms.max(0.0);
+                h.noise_width = s.noise_width;
+
                 dst[i] = h;
             }
         }
@@ -156,6 +517,19 @@ fn hz_for_semitone(base: f32, semi: i32) -> f32 {
     base * (2.0f32).powf(semi as f32 / 12.0)
 }
 
+/// Overshoot of the exponential segments' targets, as a fraction of the
+/// span. The curve reaches the real target exactly at the segment time and
+/// is then clamped; a small ratio means a sharper curve.
+const EXP_ATTACK_RATIO: f32 = 0.3;
+const EXP_DR_RATIO: f32 = 0.0001;
+
+/// One-pole step toward `aim` (the target pushed out by `ratio` of the span)
+/// so a full span takes `secs`.
+fn exp_step(level: f32, aim: f32, ratio: f32, step: f32, secs: f32) -> f32 {
+    let coef = (-((1.0 + ratio) / ratio).ln() * step / secs).exp();
+    aim + (level - aim) * coef
+}
+
 fn step_env(ch: &mut HostCh, step: f32) {
     let a = ch.a_ms / 1000.0;
     let d = ch.d_ms / 1000.0;
@@ -172,16 +546,19 @@ fn step_env(ch: &mut HostCh, step: f32) {
     }
     ch.gate_prev = ch.gate;
 
+    let exp = ch.env_curve == EnvCurve::Exp;
     match ch.env_state {
         1 => { // Attack (0→1)
-            if a > 0.0 { ch.env_level += step / a; } else { ch.env_level = 1.0; }
+            if a <= 0.0 { ch.env_level = 1.0; }
+            else if exp { ch.env_level = exp_step(ch.env_level, 1.0 + EXP_ATTACK_RATIO, EXP_ATTACK_RATIO, step, a); }
+            else { ch.env_level += step / a; }
             if ch.env_level >= 1.0 { ch.env_level = 1.0; ch.env_state = 2; }
         }
         2 => { // Decay (1→S)
-            if d > 0.0 {
-                let delta = (1.0 - s).max(0.0);
-                ch.env_level -= (step / d) * delta;
-            } else { ch.env_level = s; }
+            let delta = (1.0 - s).max(0.0);
+            if d <= 0.0 { ch.env_level = s; }
+            else if exp { ch.env_level = exp_step(ch.env_level, s - EXP_DR_RATIO * delta, EXP_DR_RATIO, step, d); }
+            else { ch.env_level -= (step / d) * delta; }
             if ch.env_level <= s { ch.env_level = s; ch.env_state = 3; }
         }
         3 => { // Sustain
@@ -189,32 +566,87 @@ fn step_env(ch: &mut HostCh, step: f32) {
             if !ch.gate { ch.env_state = 4; }
         }
         4 => { // Release (→0)
-            if r > 0.0 { ch.env_level -= (step / r) * ch.env_level.max(0.0); }
-            else { ch.env_level = 0.0; }
+            if r <= 0.0 { ch.env_level = 0.0; }
+            else if exp { ch.env_level = exp_step(ch.env_level, -EXP_DR_RATIO, EXP_DR_RATIO, step, r); }
+            else { ch.env_level -= (step / r) * ch.env_level.max(0.0); }
             if ch.env_level <= 0.0 { ch.env_level = 0.0; ch.env_state = 0; }
         }
         _ => { ch.env_level = if ch.gate { 1.0 } else { 0.0 }; }
     }
 }
 
-fn fill_buffer(out: &mut [f32], sr: f32, channels: &Arc<Mutex<[HostCh; 4]>>, t_counter: &mut usize) {
-    // 1) state snapshot
+/// One shift of the noise LFSR (Game Boy style: bit0 ^ bit1 fed back into
+/// the top bit). 15 bits repeat every 32767 steps; 7 bits every 127, which
+/// is short enough to hear as a metallic tone.
+fn lfsr_step(state: u32, width: u32) -> u32 {
+    let width = if width == 7 { 7 } else { 15 };
+    let top = width - 1;
+    let bit = (state ^ (state >> 1)) & 1;
+    let next = ((state >> 1) | (bit << top)) & ((1 << width) - 1);
+    if next == 0 { 1 << top } else { next }
+}
+
+/// Fastest full-scale gain change (ms). Gate edges with zero attack/release
+/// would otherwise jump straight to/from full amplitude and click; 2 ms is
+/// short enough that percussive hits keep their snap.
+const DECLICK_MS: f32 = 2.0;
+
+/// Time constant of the `vol`/`duty` smoothing (ms). The game sets them
+/// once per frame; stepping them instantly buzzes (zipper noise) during
+/// fades. A change is ~95% done after 3 time constants.
+const PARAM_SMOOTH_MS: f32 = 1.5;
+
+fn fill_buffer(out: &mut [f32], sr: f32, shared: &Arc<Mutex<AudioShared>>, t_counter: &mut usize) {
+    // 1) state snapshot (SFX voices are taken out and handed back at the end)
     let mut loc = [HostCh::default(); 4];
-    if let std::result::Result::Ok(src) = channels.lock() {
-        loc.copy_from_slice(&*src);
+    let mut sfx: [Option<SfxVoice>; SFX_VOICES] = Default::default();
+    let mut audible = [true; 4];
+    let mut wave = Wavetable::default();
+    let mut delay = Delay::default();
+    let mut lowpass = LowPass::default();
+    let mut crush = Crusher::default();
+    let (mut master, mut master_target) = (1.0, 1.0);
+    if let std::result::Result::Ok(mut src) = shared.lock() {
+        loc = src.chans;
+        wave = src.wave;
+        if let Some(d) = src.new_delay.take() { src.old_delay = Some(std::mem::replace(&mut src.delay, d)); }
+        delay = std::mem::take(&mut src.delay);
+        lowpass = src.lowpass;
+        crush = src.crush;
+        master = src.master_gain;
+        master_target = if src.master_mute { 0.0 } else { 1.0 };
+        sfx = std::mem::take(&mut src.sfx);
+        audible = std::array::from_fn(|i| src.audible(i));
     }
 
     let step = 1.0 / sr;
+    let max_gain_step = step * 1000.0 / DECLICK_MS;
+    let smooth = 1.0 - (-step * 1000.0 / PARAM_SMOOTH_MS).exp();
 
     for frame in out.chunks_exact_mut(2) {
         let mut mix = 0.0f32;
 
-        for ch in loc.iter_mut() {
+        for (ch, &audible) in loc.iter_mut().zip(&audible) {
             // Envelope
+            let retrigger = ch.gate && !ch.gate_prev;
             step_env(ch, step);
 
+            // Portamento: a new base_freq is reached over portamento_ms, but
+            // a fresh note (retrigger or silent voice) starts on pitch
+            if ch.base_freq != ch.glide_target {
+                let dist = (ch.base_freq - ch.glide_freq).abs();
+                ch.glide_target = ch.base_freq;
+                ch.glide_rate = if ch.portamento_ms > 0.0 { dist * 1000.0 / ch.portamento_ms } else { f32::INFINITY };
+            }
+            if retrigger || ch.env_state == 0 {
+                ch.glide_freq = ch.glide_target;
+            } else {
+                let max = ch.glide_rate * step;
+                ch.glide_freq += (ch.glide_target - ch.glide_freq).clamp(-max, max);
+            }
+
             // Arpeggio
-            let mut freq = ch.base_freq;
+            let mut freq = ch.glide_freq;
             if ch.arp_rate_hz > 0.0 {
                 ch.arp_phase += step * ch.arp_rate_hz;
                 if ch.arp_phase >= 1.0 { ch.arp_phase -= 1.0; }
@@ -227,52 +659,990 @@ fn fill_buffer(out: &mut [f32], sr: f32, channels: &Arc<Mutex<[HostCh; 4]>>, t_c
                 if semi != 0 { freq = hz_for_semitone(freq, semi); }
             }
 
-            let amp = (ch.vol * ch.env_level).clamp(0.0, 1.0);
+            // vol/duty smoothing; a silent voice can jump straight to its settings
+            if ch.out_gain <= 0.0 {
+                ch.vol_smooth = ch.vol;
+                ch.duty_smooth = ch.duty;
+            } else {
+                ch.vol_smooth += (ch.vol - ch.vol_smooth) * smooth;
+                ch.duty_smooth += (ch.duty - ch.duty_smooth) * smooth;
+            }
+
+            // muted voices keep running (phase, envelope) and just fade out,
+            // so unmuting mid-note doesn't pop
+            let target = if audible { (ch.vol_smooth * ch.env_level).clamp(0.0, 1.0) } else { 0.0 };
+            ch.out_gain += (target - ch.out_gain).clamp(-max_gain_step, max_gain_step);
+            let amp = ch.out_gain;
             if amp <= 0.0001 { continue; }
 
             match ch.kind {
                 0 | 1 => {
                     ch.phase += freq * step;
                     if ch.phase >= 1.0 { ch.phase -= 1.0; }
-                    let s = if ch.phase < ch.duty { 1.0 } else { -1.0 };
+                    let s = if ch.phase < ch.duty_smooth { 1.0 } else { -1.0 };
                     mix += s * amp;
                 }
                 2 => { // noise
                     let nsteps = (sr / freq.max(1.0)).max(1.0) as u32;
-                    if *t_counter as u32 % nsteps == 0 {
-                        let bit = ((ch.noise ^ (ch.noise >> 1)) & 1) as u32;
-                        ch.noise = ((ch.noise >> 1) | (bit << 14)) & 0x7FFF;
-                        if ch.noise == 0 { ch.noise = 0x4000; }
+                    if (*t_counter as u32).is_multiple_of(nsteps) {
+                        ch.noise = lfsr_step(ch.noise, ch.noise_width);
                     }
                     let s = if (ch.noise & 1) != 0 { 1.0 } else { -1.0 };
                     mix += s * amp;
                 }
+                7 => { // wavetable
+                    ch.phase += freq * step;
+                    if ch.phase >= 1.0 { ch.phase -= 1.0; }
+                    mix += wave.sample(ch.phase) * amp;
+                }
                 _ => {}
             }
         }
 
         *t_counter = t_counter.wrapping_add(1);
-        mix = (mix * 0.25).clamp(-1.0, 1.0); // headroom
-        frame[0] = mix;
-        frame[1] = mix;
+        mix *= 0.25; // headroom
+
+        // one-shot samples, mixed at full level on top of the synth
+        let (mut l, mut r) = (mix, mix);
+        for v in sfx.iter_mut().flatten() {
+            if let Some(&[sfx_l, sfx_r]) = v.data.get(v.pos) {
+                l += sfx_l;
+                r += sfx_r;
+                v.pos += 1;
+            }
+        }
+        let (l, r) = crush.process(l, r);
+        let (l, r) = delay.process(l, r);
+        let (l, r) = lowpass.process(l, r);
+        master += (master_target - master).clamp(-max_gain_step, max_gain_step);
+        let (l, r) = (l * master, r * master);
+        frame[0] = l.clamp(-1.0, 1.0);
+        frame[1] = r.clamp(-1.0, 1.0);
+    }
+
+    // 3) return updated state (phase, env, arp…) to engine, plus the SFX
+    //    still playing (voices triggered meanwhile win over older ones)
+    if let std::result::Result::Ok(mut dst) = shared.lock() {
+        dst.chans = loc;
+        dst.delay = delay;
+        dst.master_gain = master;
+        dst.clock_frames += dst.clock_buf;
+        dst.clock_buf = (out.len() / 2) as u64;
+        dst.clock_at = Some(Instant::now());
+        // keep only the filter memory: the cutoff may have changed meanwhile
+        if dst.lowpass.coef != 0.0 { dst.lowpass.y = lowpass.y; }
+        if (dst.crush.bits, dst.crush.rate_div) == (crush.bits, crush.rate_div) { dst.crush = crush; }
+        for v in sfx.into_iter().flatten().filter(|v| v.pos < v.data.len()) {
+            dst.add_sfx(v);
+        }
+    }
+}
+
+/// Decodes the cart's SFX and converts them to `rate` (kept as-is when
+/// there's no audio device). Any unreadable or unsupported file is an error.
+fn load_sfx(paths: &[std::path::PathBuf], rate: Option<u32>) -> Result<Vec<SfxData>> {
+    paths.iter()
+        .map(|path| {
+            let wav = fs::read(path).map_err(Error::from)
+                .and_then(|bytes| crate::wav::parse(&bytes))
+                .with_context(|| format!("sfx {}", path.display()))?;
+            let frames = match rate {
+                Some(r) => crate::wav::resample(&wav.frames, wav.sample_rate, r),
+                None => wav.frames,
+            };
+            Ok(Arc::new(frames))
+        })
+        .collect()
+}
+
+/// Largest single file `load_assets` keeps (bytes); bigger ones are skipped.
+pub const ASSET_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Files under `dir` (recursively) for `oxido_asset_*`, keyed by their path
+/// relative to `dir` with '/' separators ("levels/1.txt"). A missing folder
+/// is just an empty map; unreadable or oversized files are skipped with a warning.
+fn load_assets(dir: &std::path::Path) -> HashMap<String, Vec<u8>> {
+    fn walk(root: &std::path::Path, dir: &std::path::Path, out: &mut HashMap<String, Vec<u8>>) {
+        let std::result::Result::Ok(entries) = fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() { walk(root, &path, out); continue; }
+            let name = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            match fs::metadata(&path) {
+                std::result::Result::Ok(m) if m.len() > ASSET_MAX_BYTES => {
+                    eprintln!("⚠️  OxidoBoy: asset {name} is over {} MiB; skipped", ASSET_MAX_BYTES >> 20);
+                }
+                _ => match fs::read(&path) {
+                    std::result::Result::Ok(bytes) => { out.insert(name, bytes); }
+                    Err(e) => eprintln!("⚠️  OxidoBoy: asset {name} unreadable ({e}); skipped"),
+                },
+            }
+        }
+    }
+    let mut out = HashMap::new();
+    walk(dir, dir, &mut out);
+    out
+}
+
+// ===================== Host imports (guest → host) ========================
+
+/// Largest blob accepted by `oxido_save` (bytes).
+pub const SAVE_MAX_BYTES: usize = 64 * 1024;
+
+/// Largest framebuffer side accepted by `oxido_resize` (pixels).
+pub const FB_MAX_SIDE: u32 = 1024;
+
+/// Per-instance state owned by the wasm `Store`, reachable from host imports.
+#[derive(Clone)]
+struct HostState {
+    save_path: Option<std::path::PathBuf>,
+    /// Time zero for `oxido_now_ms`; shared with the frame loop and kept across reloads.
+    start: Instant,
+    /// Framebuffer size asked for by `oxido_resize`, applied by the frame loop.
+    resize_request: Option<(u32, u32)>,
+    /// Latest `oxido_flash(color, ms)`, picked up by the frame loop.
+    flash_request: Option<(u32, u32)>,
+    /// Cursor in framebuffer pixels, refreshed by the frame loop before `oxido_update`.
+    mouse_pos: (u32, u32),
+    /// Bit 0 = left, 1 = right, 2 = middle.
+    mouse_buttons: u32,
+    /// `None` when running muted (no device) or headless.
+    sfx: Option<SfxBank>,
+    /// Backs `oxido_beat_phase`; `None` (no audio) falls back to `start`.
+    audio_clock: Option<AudioClock>,
+    /// The cart's `assets/` files by name, for `oxido_asset_len/read`.
+    assets: Arc<HashMap<String, Vec<u8>>>,
+    /// Frames whose update ran before the current one, for `oxido_frame_count`.
+    frame_count: u64,
+    /// Time at the start of the current frame, for `oxido_uptime_ms`.
+    uptime_ms: f64,
+}
+
+impl HostState {
+    fn new(save_path: Option<std::path::PathBuf>, start: Instant) -> Self {
+        Self { save_path, start, resize_request: None, flash_request: None, mouse_pos: (0, 0), mouse_buttons: 0, sfx: None, audio_clock: None, assets: Arc::default(), frame_count: 0, uptime_ms: 0.0 }
+    }
+}
+
+/// Points both clear paths (the default scaler and the filter pass) at `bg`.
+fn apply_background(pixels: &mut Pixels, post: Option<&mut PostProcess>, bg: Background) {
+    let color = bg.to_wgpu(pixels.render_texture_format());
+    pixels.clear_color(color);
+    if let Some(pp) = post { pp.set_background(color); }
+}
+
+/// Copies `len` bytes at `ptr` out of the guest's exported memory.
+/// Returns `None` if there is no memory export or the range is out of bounds.
+fn guest_bytes(caller: &mut Caller<'_, HostState>, ptr: u32, len: u32) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let start = ptr as usize;
+    let end = start.checked_add(len as usize)?;
+    memory.data(&caller).get(start..end).map(|s| s.to_vec())
+}
+
+fn register_host_imports(linker: &mut Linker<HostState>) -> Result<()> {
+    // oxido_log(ptr, len): UTF-8 message printed to stderr
+    linker.func_wrap("env", "oxido_log", |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| {
+        match guest_bytes(&mut caller, ptr, len) {
+            Some(bytes) => eprintln!("[game] {}", String::from_utf8_lossy(&bytes)),
+            None => eprintln!("[game] <invalid log range ptr={ptr} len={len}>"),
+        }
+    })?;
+
+    // oxido_save(ptr, len) -> 1 on success, 0 on failure
+    linker.func_wrap("env", "oxido_save", |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> u32 {
+        let Some(path) = caller.data().save_path.clone() else { return 0 };
+        if len as usize > SAVE_MAX_BYTES {
+            eprintln!("⚠️  OxidoBoy: save of {len} bytes exceeds the {SAVE_MAX_BYTES} byte limit");
+            return 0;
+        }
+        let Some(bytes) = guest_bytes(&mut caller, ptr, len) else { return 0 };
+        match write_atomic(&path, &bytes) {
+            std::result::Result::Ok(()) => 1,
+            Err(e) => { eprintln!("⚠️  OxidoBoy: could not write {}: {e}", path.display()); 0 }
+        }
+    })?;
+
+    // oxido_now_ms() -> monotonic ms since the runtime started (real time, not summed dt)
+    linker.func_wrap("env", "oxido_now_ms", |caller: Caller<'_, HostState>| -> f64 {
+        caller.data().start.elapsed().as_secs_f64() * 1000.0
+    })?;
+
+    // oxido_frame_count() -> frames updated before this one (0 during init and the first update)
+    linker.func_wrap("env", "oxido_frame_count", |caller: Caller<'_, HostState>| -> u64 {
+        caller.data().frame_count
+    })?;
+
+    // oxido_uptime_ms() -> ms since start, taken once per frame (same value all frame long)
+    linker.func_wrap("env", "oxido_uptime_ms", |caller: Caller<'_, HostState>| -> f64 {
+        caller.data().uptime_ms
+    })?;
+
+    // oxido_resize(w, h) -> 1 if accepted; the new size applies from the next draw
+    linker.func_wrap("env", "oxido_resize", |mut caller: Caller<'_, HostState>, w: u32, h: u32| -> u32 {
+        if w == 0 || h == 0 || w > FB_MAX_SIDE || h > FB_MAX_SIDE {
+            eprintln!("⚠️  OxidoBoy: oxido_resize({w}, {h}) ignored (each side must be 1..={FB_MAX_SIDE})");
+            return 0;
+        }
+        caller.data_mut().resize_request = Some((w, h));
+        1
+    })?;
+
+    // oxido_flash(color, ms): color over the whole screen, fading out over ms
+    linker.func_wrap("env", "oxido_flash", |mut caller: Caller<'_, HostState>, color: u32, ms: u32| {
+        caller.data_mut().flash_request = Some((color, ms));
+    })?;
+
+    // oxido_mouse_pos() -> x | y<<32, in framebuffer pixels
+    linker.func_wrap("env", "oxido_mouse_pos", |caller: Caller<'_, HostState>| -> u64 {
+        let (x, y) = caller.data().mouse_pos;
+        x as u64 | (y as u64) << 32
+    })?;
+
+    // oxido_mouse_buttons() -> held buttons (bit 0 left, 1 right, 2 middle)
+    linker.func_wrap("env", "oxido_mouse_buttons", |caller: Caller<'_, HostState>| -> u32 {
+        caller.data().mouse_buttons
+    })?;
+
+    // oxido_play_sfx(id) -> 1 if sound `id` (index into the manifest's `sfx`) started
+    linker.func_wrap("env", "oxido_play_sfx", |caller: Caller<'_, HostState>, id: u32| -> u32 {
+        let Some(bank) = &caller.data().sfx else { return 0 };
+        let Some(data) = bank.sounds.get(id as usize) else { return 0 };
+        let std::result::Result::Ok(mut mixer) = bank.mixer.lock() else { return 0 };
+        mixer.sfx_seq += 1;
+        let seq = mixer.sfx_seq;
+        mixer.add_sfx(SfxVoice { data: data.clone(), pos: 0, seq });
+        1
+    })?;
+
+    // oxido_beat_phase(bpm) -> position within the current beat (0..1), from
+    // the audio clock so visuals stay locked to what's heard
+    linker.func_wrap("env", "oxido_beat_phase", |caller: Caller<'_, HostState>, bpm: f32| -> f32 {
+        if !(bpm > 0.0 && bpm.is_finite()) { return 0.0; }
+        let host = caller.data();
+        let secs = host.audio_clock.as_ref().and_then(AudioClock::secs)
+            .unwrap_or_else(|| host.start.elapsed().as_secs_f64());
+        (secs * bpm as f64 / 60.0).fract() as f32
+    })?;
+
+    // oxido_asset_len(name_ptr, name_len) -> size of asset `name`, u32::MAX if there's none
+    linker.func_wrap("env", "oxido_asset_len", |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> u32 {
+        let Some(name) = guest_bytes(&mut caller, ptr, len) else { return u32::MAX };
+        let name = String::from_utf8_lossy(&name);
+        match caller.data().assets.get(name.as_ref()) {
+            Some(bytes) => bytes.len() as u32,
+            None => { eprintln!("⚠️  OxidoBoy: no asset named \"{name}\""); u32::MAX }
+        }
+    })?;
+
+    // oxido_asset_read(name_ptr, name_len, dst, max_len) -> bytes copied (0 if missing)
+    linker.func_wrap("env", "oxido_asset_read", |mut caller: Caller<'_, HostState>, ptr: u32, len: u32, dst: u32, max_len: u32| -> u32 {
+        let Some(name) = guest_bytes(&mut caller, ptr, len) else { return 0 };
+        let assets = caller.data().assets.clone();
+        let Some(bytes) = assets.get(String::from_utf8_lossy(&name).as_ref()) else { return 0 };
+        let n = bytes.len().min(max_len as usize);
+        let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else { return 0 };
+        let start = dst as usize;
+        match memory.data_mut(&mut caller).get_mut(start..start + n) {
+            Some(out) => { out.copy_from_slice(&bytes[..n]); n as u32 }
+            None => 0,
+        }
+    })?;
+
+    // oxido_load(ptr, max_len) -> bytes copied into guest memory (0 if no save)
+    linker.func_wrap("env", "oxido_load", |mut caller: Caller<'_, HostState>, ptr: u32, max_len: u32| -> u32 {
+        let Some(path) = caller.data().save_path.clone() else { return 0 };
+        let std::result::Result::Ok(bytes) = fs::read(&path) else { return 0 };
+        let n = bytes.len().min(max_len as usize);
+        let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else { return 0 };
+        let start = ptr as usize;
+        match memory.data_mut(&mut caller).get_mut(start..start + n) {
+            Some(dst) => { dst.copy_from_slice(&bytes[..n]); n as u32 }
+            None => 0,
+        }
+    })?;
+    Ok(())
+}
+
+/// Writes to a sibling temp file and renames it over `path`, so a crash
+/// mid-write never leaves a truncated save behind.
+fn write_atomic(path: &std::path::Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("sav.tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
+
+// ===================== Guest (wasm instance + ABI) ========================
+
+/// A live guest instance and the ABI exports the frame loop calls.
+struct Guest {
+    store: Store<HostState>,
+    _instance: Instance,
+    memory: Memory,
+    init: TypedFunc<(), ()>,
+    update: TypedFunc<f32, ()>,
+    draw_ptr: TypedFunc<(), u32>,
+    draw_len: TypedFunc<(), u32>,
+    input_set: TypedFunc<u32, ()>,
+    audio_ptr: Option<TypedFunc<(), u32>>, // audio_state_ptr
+    audio_len: Option<TypedFunc<(), u32>>, // audio_state_len (bytes)
+    wave_ptr: Option<TypedFunc<(), u32>>,  // wavetable for kind 7 (f32 samples)
+    wave_len: Option<TypedFunc<(), u32>>,  // its size in bytes
+    dirty_rect: Option<TypedFunc<(), u64>>, // packed x,y,w,h of what changed
+    serialize_ptr: Option<TypedFunc<(), u32>>, // state snapshot kept across reloads
+    serialize_len: Option<TypedFunc<(), u32>>,
+    deserialize: Option<TypedFunc<(u32, u32), ()>>,
+    should_exit: Option<TypedFunc<(), u32>>, // nonzero = close the runtime
+}
+
+impl Guest {
+    /// Resets the per-call instruction budget (no-op when fuel is disabled).
+    fn refuel(&mut self, fuel: u64) {
+        if fuel > 0 { let _ = self.store.set_fuel(fuel); }
+    }
+
+    /// Polls the optional `oxido_should_exit`; false without the export or if
+    /// the call traps.
+    fn wants_exit(&mut self, fuel: u64) -> bool {
+        let Some(f) = self.should_exit.clone() else { return false };
+        self.refuel(fuel);
+        f.call(&mut self.store, ()).is_ok_and(|v| v != 0)
+    }
+
+    /// Copy of the bytes behind `oxido_serialize_ptr/len`; `None` if the guest
+    /// doesn't export them or the call/range fails.
+    fn snapshot_state(&mut self) -> Option<Vec<u8>> {
+        let ptr = self.serialize_ptr.as_ref()?.call(&mut self.store, ()).ok()?;
+        let len = self.serialize_len.as_ref()?.call(&mut self.store, ()).ok()?;
+        guest_slice(&self.memory, &self.store, ptr as usize, len as usize).map(|s| s.to_vec())
     }
 
-    // 3) return updated state (phase, env, arp…) to engine
-    if let std::result::Result::Ok(mut dst) = channels.lock() {
-        *dst = loc;
+    /// Writes `state` into fresh pages past the guest's heap (its allocator
+    /// never hands those out) and calls `oxido_deserialize(ptr, len)`.
+    fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        let deserialize = self.deserialize.clone().context("no oxido_deserialize export")?;
+        let ptr = self.memory.data_size(&self.store);
+        let pages = state.len().div_ceil(65536) as u64;
+        self.memory.grow(&mut self.store, pages)?;
+        self.memory.write(&mut self.store, ptr, state)?;
+        deserialize.call(&mut self.store, (ptr as u32, state.len() as u32))
     }
 }
 
+/// `fuel == 0` disables metering; otherwise every guest call can be trapped.
+fn make_engine(fuel: u64) -> Result<Engine> {
+    let mut config = Config::new();
+    config.consume_fuel(fuel > 0);
+    Engine::new(&config)
+}
+
+/// Exports every cart must have; see the README ABI section.
+const REQUIRED_EXPORTS: [&str; 6] = [
+    "memory", "oxido_init", "oxido_update", "oxido_draw_ptr", "oxido_draw_len", "oxido_input_set",
+];
+
+fn instantiate_all(engine: &Engine, wasm_path: &std::path::Path, host: HostState) -> Result<Guest> {
+    let module = Module::from_file(engine, wasm_path)?;
+    let mut linker = Linker::new(engine);
+    register_host_imports(&mut linker)?;
+    let mut store = Store::new(engine, host);
+    let instance = linker.instantiate(&mut store, &module)?;
+
+    // report every missing export at once instead of failing on the first
+    let missing: Vec<&str> = REQUIRED_EXPORTS.iter().copied()
+        .filter(|name| instance.get_export(&mut store, name).is_none())
+        .collect();
+    if !missing.is_empty() {
+        bail!("missing exports: {}", missing.join(", "));
+    }
+
+    let memory   = instance.get_memory(&mut store, "memory").context("`memory` export is not a memory")?;
+    let init     = instance.get_typed_func::<(), ()>(&mut store, "oxido_init").context("wrong signature for oxido_init")?;
+    let update   = instance.get_typed_func::<f32, ()>(&mut store, "oxido_update").context("wrong signature for oxido_update")?;
+    let draw_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_draw_ptr").context("wrong signature for oxido_draw_ptr")?;
+    let draw_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_draw_len").context("wrong signature for oxido_draw_len")?;
+    let input_set= instance.get_typed_func::<u32, ()>(&mut store, "oxido_input_set").context("wrong signature for oxido_input_set")?;
+
+    let audio_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_audio_state_ptr").ok();
+    let audio_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_audio_state_len").ok();
+    match (&audio_ptr, &audio_len) {
+        (Some(_), None) => eprintln!("⚠️  OxidoBoy: oxido_audio_state_ptr without oxido_audio_state_len; audio disabled"),
+        (None, Some(_)) => eprintln!("⚠️  OxidoBoy: oxido_audio_state_len without oxido_audio_state_ptr; audio disabled"),
+        _ => {}
+    }
+    let wave_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_wave_ptr").ok();
+    let wave_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_wave_len").ok();
+    let dirty_rect = instance.get_typed_func::<(), u64>(&mut store, "oxido_dirty_rect").ok();
+    let serialize_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_serialize_ptr").ok();
+    let serialize_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_serialize_len").ok();
+    let deserialize = instance.get_typed_func::<(u32, u32), ()>(&mut store, "oxido_deserialize").ok();
+    let should_exit = instance.get_typed_func::<(), u32>(&mut store, "oxido_should_exit").ok();
+
+    Ok(Guest {
+        store, _instance: instance, memory, init, update, draw_ptr, draw_len, input_set,
+        audio_ptr, audio_len, wave_ptr, wave_len, dirty_rect, serialize_ptr, serialize_len, deserialize,
+        should_exit,
+    })
+}
+
+/// Bounds-checked view of guest memory; `None` if `ptr+len` is past its current size.
+fn guest_slice<'a>(memory: &Memory, store: &'a Store<HostState>, ptr: usize, len: usize) -> Option<&'a [u8]> {
+    memory.data(store).get(ptr..ptr.checked_add(len)?)
+}
+
+/// (x, y, w, h) in framebuffer pixels.
+pub type PixelRect = (usize, usize, usize, usize);
+
+/// Decodes `oxido_dirty_rect` (x | y<<16 | w<<32 | h<<48, u16 each) and
+/// clips it to the framebuffer.
+fn clip_dirty_rect(v: u64, fw: u32, fh: u32) -> PixelRect {
+    let [x, y, w, h] = [0, 16, 32, 48].map(|sh| ((v >> sh) & 0xFFFF) as u32);
+    let (x0, y0) = (x.min(fw), y.min(fh));
+    let (x1, y1) = ((x + w).min(fw), (y + h).min(fh));
+    (x0 as usize, y0 as usize, (x1 - x0) as usize, (y1 - y0) as usize)
+}
+
+/// Copies the (x, y, w, h) rect of a `fw`-pixel-wide RGBA frame from `src`
+/// into `dst` (same size), applying the DMG remap to the copied rows only.
+pub fn copy_frame_rect(src: &[u8], dst: &mut [u8], fw: u32, (x, y, w, h): PixelRect, dmg: bool) {
+    let stride = fw as usize * 4;
+    for row in y..y + h {
+        let span = row * stride + x * 4..row * stride + (x + w) * 4;
+        dst[span.clone()].copy_from_slice(&src[span.clone()]);
+        if dmg { apply_dmg(&mut dst[span]); }
+    }
+}
+
+fn is_out_of_fuel(e: &Error) -> bool {
+    matches!(e.downcast_ref::<Trap>(), Some(Trap::OutOfFuel))
+}
+
+/// Blends `color` (RGBA as the SDK's `rgba`, its alpha scaled by `amount`
+/// 0..=1) over every pixel.
+fn apply_flash(frame: &mut [u8], color: u32, amount: f32) {
+    let c = color.to_le_bytes();
+    let a = (c[3] as f32 / 255.0 * amount).clamp(0.0, 1.0);
+    if a <= 0.0 { return; }
+    for px in frame.chunks_exact_mut(4) {
+        for i in 0..3 {
+            px[i] = (px[i] as f32 + (c[i] as f32 - px[i] as f32) * a).round() as u8;
+        }
+    }
+}
+
+/// Input bitfield bit for a key (0 if unmapped). Virtual keycodes only:
+/// raw scancodes differ per OS.
+pub(crate) fn input_bit(key: Option<VirtualKeyCode>) -> u32 {
+    match key {
+        Some(VirtualKeyCode::Up)    => 1 << 0,
+        Some(VirtualKeyCode::Down)  => 1 << 1,
+        Some(VirtualKeyCode::Left)  => 1 << 2,
+        Some(VirtualKeyCode::Right) => 1 << 3,
+        Some(VirtualKeyCode::Z)     => 1 << 4,
+        Some(VirtualKeyCode::X)     => 1 << 5,
+        Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) => 1 << 6,
+        Some(VirtualKeyCode::LShift)| Some(VirtualKeyCode::RShift) => 1 << 7,
+        _ => 0,
+    }
+}
+
+/// Red banner with the trap message, drawn over the frozen framebuffer.
+fn draw_trap_banner(frame: &mut [u8], w: u32, h: u32, msg: &str) {
+    const MAX_LINES: usize = 4;
+    let mut f = oxido_sdk::Frame { data: frame, w: w as usize, h: h as usize };
+    let cols = (f.w.saturating_sub(4) / 6).max(1);
+    let text: Vec<char> = format!("TRAP: {msg}").chars().collect();
+    let lines: Vec<String> = text.chunks(cols).take(MAX_LINES).map(|c| c.iter().collect()).collect();
+
+    let banner_h = lines.len() as i32 * 9 + 3;
+    f.rect(0, 0, w as i32, banner_h, oxido_sdk::rgba(160, 16, 16, 255));
+    for (i, line) in lines.iter().enumerate() {
+        f.text5x7(2, 2 + i as i32 * 9, line, oxido_sdk::rgba(255, 255, 255, 255));
+    }
+}
+
+/// F3 overlay: one row per audio channel with its envelope level and the
+/// pitch it's playing. Muted (or not soloed) channels are dimmed.
+fn draw_audio_meters(frame: &mut [u8], w: u32, h: u32, chans: &[HostCh; 4], muted: &[bool; 4], soloed: &[bool; 4]) {
+    const ROW: i32 = 9;
+    let mut f = oxido_sdk::Frame { data: frame, w: w as usize, h: h as usize };
+    let any_solo = soloed.iter().any(|&s| s);
+    let (bright, dim) = (oxido_sdk::rgba(255, 255, 255, 255), oxido_sdk::rgba(110, 110, 110, 255));
+    let (bg, bar_bg) = (oxido_sdk::rgba(0, 0, 0, 255), oxido_sdk::rgba(40, 40, 40, 255));
+    f.rect(0, 0, 104, ROW * 4 + 2, bg);
+    for (i, ch) in chans.iter().enumerate() {
+        let y = 2 + i as i32 * ROW;
+        let audible = if any_solo { soloed[i] } else { !muted[i] };
+        let color = if audible { bright } else { dim };
+        let kind = match ch.kind { 0 | 1 => 'P', 2 => 'N', 7 => 'W', _ => '?' };
+        let flag = if soloed[i] { "S" } else if muted[i] { "M" } else { "" };
+        f.text5x7(2, y, &format!("{}{kind}{flag}", i + 1), color);
+        let fg = if ch.gate { oxido_sdk::rgba(80, 220, 120, 255) } else { oxido_sdk::rgba(220, 160, 60, 255) };
+        f.bar(22, y, 40, 7, ch.env_level, if audible { fg } else { dim }, bar_bg, None);
+        if ch.env_level > 0.0 {
+            f.text5x7(66, y, &format!("{:.0}", ch.glide_freq), color);
+        }
+    }
+}
+
+/// Frames between rewind snapshots (15 a second at 60 Hz).
+const REWIND_EVERY: u32 = 4;
+
+/// `--rewind-seconds`: copies of the whole guest memory (plus the
+/// framebuffer size) taken every `REWIND_EVERY` frames, oldest dropped first.
+struct Rewind {
+    snaps: VecDeque<(Vec<u8>, (u32, u32))>,
+    cap: usize,
+    tick: u32,
+}
+
+impl Rewind {
+    fn new(seconds: u32) -> Self {
+        let cap = (seconds.saturating_mul(60) / REWIND_EVERY).max(1) as usize;
+        Self { snaps: VecDeque::with_capacity(cap), cap, tick: 0 }
+    }
+
+    /// Call once per played frame.
+    fn record(&mut self, guest: &Guest, size: (u32, u32)) {
+        self.tick += 1;
+        if self.tick < REWIND_EVERY { return; }
+        self.tick = 0;
+        // once full, the oldest buffer is reused instead of reallocated
+        let mut buf = if self.snaps.len() == self.cap {
+            self.snaps.pop_front().map(|(b, _)| b).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        buf.clear();
+        buf.extend_from_slice(guest.memory.data(&guest.store));
+        self.snaps.push_back((buf, size));
+    }
+
+    /// Call once per frame while rewinding: every `REWIND_EVERY` frames the
+    /// newest snapshot is dropped and the one before it written back (so it
+    /// plays back at recording speed); returns that snapshot's framebuffer
+    /// size. The oldest one is kept, so holding the key past the start just
+    /// stays there.
+    fn step_back(&mut self, guest: &mut Guest) -> Option<(u32, u32)> {
+        self.tick += 1;
+        if self.tick < REWIND_EVERY { return None; }
+        self.tick = 0;
+        if self.snaps.len() > 1 { self.snaps.pop_back(); }
+        let (mem, size) = self.snaps.back()?;
+        let data = guest.memory.data_mut(&mut guest.store);
+        // memory only grows; pages added after the snapshot go back to zero
+        let (old, new) = data.split_at_mut(mem.len().min(data.len()));
+        old.copy_from_slice(&mem[..old.len()]);
+        new.fill(0);
+        Some(*size)
+    }
+
+    /// Bytes the ring will hold once full, at the current memory size.
+    fn budget(&self, guest: &Guest) -> usize {
+        guest.memory.data_size(&guest.store) * self.cap
+    }
+}
+
+/// `--profile` timings of one guest export.
+#[derive(Default)]
+struct CallStats {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+    avg_ms: f32, // rolling (exponential, ~1 s at 60 Hz)
+}
+
+impl CallStats {
+    fn record(&mut self, d: Duration) {
+        let ms = d.as_secs_f32() * 1000.0;
+        self.avg_ms = if self.calls == 0 { ms } else { self.avg_ms + (ms - self.avg_ms) / 60.0 };
+        self.calls += 1;
+        self.total += d;
+        self.max = self.max.max(d);
+    }
+}
+
+/// Summary table for `--profile`, printed when the window closes.
+fn print_profile(update: &CallStats, draw: &CallStats) {
+    let all = (update.total + draw.total).as_secs_f64().max(f64::EPSILON);
+    eprintln!("📊 OxidoBoy profile");
+    eprintln!("   {:<15} {:>8} {:>9} {:>9} {:>9} {:>6}", "call", "calls", "avg ms", "max ms", "total s", "share");
+    for (name, s) in [("oxido_update", update), ("oxido_draw_ptr", draw)] {
+        let avg = if s.calls > 0 { s.total.as_secs_f64() * 1000.0 / s.calls as f64 } else { 0.0 };
+        eprintln!(
+            "   {:<15} {:>8} {:>9.3} {:>9.3} {:>9.2} {:>5.0}%",
+            name, s.calls, avg, s.max.as_secs_f64() * 1000.0, s.total.as_secs_f64(), s.total.as_secs_f64() / all * 100.0
+        );
+    }
+}
+
+/// Frames kept for the F2 graph (one pixel column each).
+const FRAME_GRAPH_LEN: usize = 64;
+
+/// F1/F2 overlay in the top-right corner: `lines` of text (FPS, profile)
+/// and/or a strip of recent frame times. Bars over a 60 Hz frame are red;
+/// the graph tops out at two frames.
+fn draw_fps_overlay(frame: &mut [u8], w: u32, h: u32, lines: &[String], times: Option<&VecDeque<f32>>) {
+    const GRAPH_H: i32 = 20;
+    const FRAME_MS: f32 = 1000.0 / 60.0;
+    let mut f = oxido_sdk::Frame { data: frame, w: w as usize, h: h as usize };
+    let (bg, white) = (oxido_sdk::rgba(0, 0, 0, 255), oxido_sdk::rgba(255, 255, 255, 255));
+    let mut y = 0;
+    for text in lines {
+        let tw = text.len() as i32 * 6 + 3;
+        f.rect(w as i32 - tw, y, tw, 10, bg);
+        f.text5x7(w as i32 - tw + 2, y + 2, text, white);
+        y += 9;
+    }
+    if !lines.is_empty() { y += 1; }
+    if let Some(times) = times {
+        let gw = FRAME_GRAPH_LEN as i32;
+        let x0 = w as i32 - gw - 2;
+        f.rect(x0 - 1, y, gw + 3, GRAPH_H + 2, bg);
+        let base = y + 1 + GRAPH_H;
+        let (ok, slow) = (oxido_sdk::rgba(80, 220, 120, 255), oxido_sdk::rgba(230, 60, 60, 255));
+        for (i, &ms) in times.iter().enumerate() {
+            let bar = ((ms / (2.0 * FRAME_MS)).min(1.0) * GRAPH_H as f32).ceil() as i32;
+            let color = if ms > FRAME_MS * 1.05 { slow } else { ok };
+            f.rect(x0 + i as i32, base - bar, 1, bar, color);
+        }
+        // 60 Hz budget line
+        f.rect(x0, base - GRAPH_H / 2, gw, 1, oxido_sdk::rgba(120, 120, 120, 255));
+    }
+}
+
+// ===================== Headless (tests, bench) =============================
+
+/// A cart driven without window or audio: call `update` then `draw` once per frame.
+pub struct Headless {
+    guest: Guest,
+    fuel: u64,
+    w: u32,
+    h: u32,
+    /// `update` calls so far, and the sum of their `dt_ms` (the headless uptime).
+    frames: u64,
+    uptime_ms: f64,
+}
+
+impl Headless {
+    /// Instantiates `cart.wasm_path` and runs `oxido_init`.
+    pub fn new(cart: &Cartridge) -> Result<Self> {
+        let engine = make_engine(cart.fuel)?;
+        let mut host = HostState::new(cart.save_path.clone(), Instant::now());
+        if let Some(dir) = &cart.assets_dir { host.assets = Arc::new(load_assets(dir)); }
+        let mut guest = instantiate_all(&engine, &cart.wasm_path, host)?;
+        guest.refuel(cart.fuel);
+        guest.init.call(&mut guest.store, ()).context("oxido_init trapped")?;
+        Ok(Self { guest, fuel: cart.fuel, w: cart.w, h: cart.h, frames: 0, uptime_ms: 0.0 })
+    }
+
+    /// Current framebuffer size (changes if the guest calls `oxido_resize`).
+    pub fn size(&self) -> (u32, u32) { (self.w, self.h) }
+
+    /// Sets the input bits and runs `oxido_update(dt_ms)`. The guest's
+    /// uptime is the sum of the `dt_ms` passed so far, so runs are repeatable.
+    pub fn update(&mut self, input_bits: u32, dt_ms: f32) -> Result<()> {
+        let g = &mut self.guest;
+        let host = g.store.data_mut();
+        (host.frame_count, host.uptime_ms) = (self.frames, self.uptime_ms);
+        self.frames += 1;
+        self.uptime_ms += dt_ms as f64;
+        g.refuel(self.fuel);
+        g.input_set.call(&mut g.store, input_bits)?;
+        g.update.call(&mut g.store, dt_ms)?;
+        if let Some((w, h)) = g.store.data_mut().resize_request.take() {
+            (self.w, self.h) = (w, h);
+        }
+        Ok(())
+    }
+
+    /// True once the guest's `oxido_should_exit` returns nonzero (never
+    /// without the export).
+    pub fn should_exit(&mut self) -> bool { self.guest.wants_exit(self.fuel) }
+
+    /// Runs `oxido_draw_ptr/len` and returns the guest's framebuffer (w*h*4
+    /// RGBA bytes, no filter applied).
+    pub fn draw(&mut self) -> Result<&[u8]> {
+        self.draw_dirty().map(|(fb, _)| fb)
+    }
+
+    /// Like `draw`, plus the clipped (x, y, w, h) the guest reports through
+    /// `oxido_dirty_rect` (`None` without the export or if it traps).
+    pub fn draw_dirty(&mut self) -> Result<(&[u8], Option<PixelRect>)> {
+        let g = &mut self.guest;
+        g.refuel(self.fuel);
+        let ptr = g.draw_ptr.call(&mut g.store, ())? as usize;
+        let len = g.draw_len.call(&mut g.store, ())? as usize;
+        let expected = self.w as usize * self.h as usize * 4;
+        if len != expected {
+            bail!("oxido_draw_len returned {len} bytes, expected {expected} for {}x{}", self.w, self.h);
+        }
+        let rect = match &g.dirty_rect {
+            Some(f) => f.call(&mut g.store, ()).ok().map(|v| clip_dirty_rect(v, self.w, self.h)),
+            None => None,
+        };
+        let fb = guest_slice(&g.memory, &g.store, ptr, len)
+            .with_context(|| format!("draw range {ptr}+{len} is outside guest memory"))?;
+        Ok((fb, rect))
+    }
+}
+
+/// Runs `frames` frames at a fixed 60 Hz `dt`, feeding `input_script[i]` on
+/// frame `i` (0 once the script runs out), and returns every framebuffer.
+pub fn run_headless(cart: &Cartridge, frames: u32, input_script: &[u32]) -> Result<Vec<Vec<u8>>> {
+    const DT_MS: f32 = 1000.0 / 60.0;
+    let mut h = Headless::new(cart)?;
+    (0..frames as usize)
+        .map(|i| {
+            h.update(input_script.get(i).copied().unwrap_or(0), DT_MS)
+                .and_then(|()| h.draw().map(<[u8]>::to_vec))
+                .with_context(|| format!("frame {i}"))
+        })
+        .collect()
+}
+
 // ===================== Runtime (video+input+hotreload) =====================
 
+/// Manifest settings that can change while the cart runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveSettings {
+    pub title: String,
+    /// Framebuffer size; only reported (a change needs a restart).
+    pub w: u32,
+    pub h: u32,
+    pub scale: u32,
+    pub filter: Filter,
+    pub bg: Background,
+}
+
+/// Turns manifest text into settings (command-line overrides included), or
+/// says why it can't.
+pub type ManifestParser = Box<dyn Fn(&str) -> Result<LiveSettings>>;
+
+/// Temp folder a zipped cart was unpacked into; removed (with everything in
+/// it) when dropped.
+pub struct UnpackedDir(pub std::path::PathBuf);
+
+impl UnpackedDir {
+    pub fn path(&self) -> &std::path::Path { &self.0 }
+}
+
+impl Drop for UnpackedDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The cart's manifest.toml, watched while running so presentation tweaks
+/// show up without a restart.
+pub struct LiveManifest {
+    pub path: std::path::PathBuf,
+    pub parse: ManifestParser,
+}
+
 pub struct Cartridge {
+    /// Window title (the manifest's `title`, else "OxidoBoy"); the FPS
+    /// readout and notes are appended to it.
+    pub title: String,
     pub wasm_path: std::path::PathBuf,
     pub w: u32,
     pub h: u32,
-    pub scale: u32
+    pub scale: u32,
+    /// File backing `oxido_save`/`oxido_load`; `None` disables saving.
+    pub save_path: Option<std::path::PathBuf>,
+    /// Fuel (≈ wasm instructions) allowed per guest call; 0 = unlimited.
+    pub fuel: u64,
+    /// Presentation filter (scanlines/CRT run as a GPU pass at window resolution).
+    pub filter: Filter,
+    /// Integer scaling with bars, or stretch to fill the window.
+    pub scale_mode: ScaleMode,
+    /// Color of the bars around the image (and of a not-yet-drawn window).
+    pub bg: Background,
+    /// PNG shown as the window icon; a missing or invalid file only warns.
+    pub icon: Option<std::path::PathBuf>,
+    /// Output device by name; `None` uses the system default.
+    pub audio_device: Option<String>,
+    /// Output sample rate in Hz; `None` uses the device default.
+    pub sample_rate: Option<u32>,
+    /// Folder whose files the guest can read with `oxido_asset_read`
+    /// (the cart's `assets/`); `None` = no assets.
+    pub assets_dir: Option<std::path::PathBuf>,
+    /// WAV files for `oxido_play_sfx`, in id order.
+    pub sfx: Vec<std::path::PathBuf>,
+    /// Also write everything played to this WAV (stereo 32-bit float).
+    pub record_audio: Option<std::path::PathBuf>,
+    /// Time `oxido_update`/`oxido_draw_ptr` and print a summary on exit.
+    pub profile: bool,
+    /// Keep the sound playing while the window is unfocused (muted otherwise).
+    pub background_audio: bool,
+    /// Window without title bar or borders.
+    pub borderless: bool,
+    /// Ask the window manager to keep the window above others.
+    pub on_top: bool,
+    /// Accessible palette forced onto the final image (F4 cycles at runtime).
+    pub palette: Option<PaletteOverride>,
+    /// Multiplies the `dt_ms` the game sees (0.5 = slow motion); frame pacing
+    /// and audio stay real-time. Clamped to `SPEED_RANGE`.
+    pub speed: f32,
+    /// Longest real frame time passed on to the game (ms, before `speed`);
+    /// 0 = no limit.
+    pub max_dt_ms: f32,
+    /// Call `oxido_update` with this constant dt (ms), as many times per
+    /// frame as the elapsed game time covers; `None` = one variable step.
+    pub fixed_dt_ms: Option<f32>,
+    /// Echo over the whole mix; `None` = off.
+    pub echo: Option<Echo>,
+    /// Low-pass cutoff (Hz) over the whole mix; `None` = off.
+    pub lowpass_hz: Option<f32>,
+    /// Bitcrusher over the whole mix; `None` = off.
+    pub crush: Option<Crush>,
+    /// Watched for live changes; `None` for raw .wasm and zipped carts.
+    pub manifest: Option<LiveManifest>,
+    /// Seconds of play kept for rewinding (hold Backspace); 0 = off.
+    pub rewind_seconds: u32,
+    /// Where a zipped cart was unpacked; deleted with the cart (`run` drops
+    /// it on exit, since the event loop never returns).
+    pub unpacked: Option<UnpackedDir>,
+}
+
+/// Most fixed steps run in one frame. If updates are slower than real time,
+/// catching up would take ever longer frames (the "spiral of death"); past
+/// this the game slows down instead.
+const MAX_FIXED_STEPS: u32 = 20;
+
+/// Default `max_dt_ms`: after a hitch (breakpoint, window drag, disk stall)
+/// the game sees at most a 10 FPS step, i.e. it briefly runs slow instead of
+/// moving things far enough to skip through walls.
+pub const DEFAULT_MAX_DT_MS: f32 = 100.0;
+
+/// Accepted `Cartridge::speed` values.
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.05..=8.0;
+
+/// Echo settings (`--echo MS,FEEDBACK,MIX` or `[audio] echo` in the manifest).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Echo {
+    pub time_ms: f32,
+    pub feedback: f32,
+    pub mix: f32,
+}
+
+impl Echo {
+    /// Range check: time 1..=1000 ms, feedback 0..1 (below 1 or it never
+    /// fades), mix 0..=1.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if !(1.0..=1000.0).contains(&self.time_ms) { return Err(format!("echo time {} ms (expected 1..=1000)", self.time_ms)); }
+        if !(0.0..1.0).contains(&self.feedback) { return Err(format!("echo feedback {} (expected 0 up to, not including, 1)", self.feedback)); }
+        if !(0.0..=1.0).contains(&self.mix) { return Err(format!("echo mix {} (expected 0..=1)", self.mix)); }
+        std::result::Result::Ok(())
+    }
+}
+
+impl std::str::FromStr for Echo {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let usage = || format!("'{s}' is not MS,FEEDBACK,MIX (e.g. 250,0.4,0.3)");
+        let parts: Vec<f32> = s.split(',').map(|p| p.trim().parse::<f32>()).collect::<std::result::Result<_, _>>()
+            .map_err(|_| usage())?;
+        let [time_ms, feedback, mix] = parts[..] else { return Err(usage()) };
+        let echo = Echo { time_ms, feedback, mix };
+        echo.check()?;
+        std::result::Result::Ok(echo)
+    }
+}
+
+/// Bitcrusher settings (`[audio] crush` in the manifest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crush {
+    pub bits: u32,
+    pub rate_div: u32,
+}
+
+impl Crush {
+    /// Range check: bits 1..=16, rate_div 1..=64.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if !(1..=16).contains(&self.bits) { return Err(format!("crush bits {} (expected 1..=16)", self.bits)); }
+        if !(1..=64).contains(&self.rate_div) { return Err(format!("crush rate_div {} (expected 1..=64)", self.rate_div)); }
+        std::result::Result::Ok(())
+    }
+}
+
+/// Default per-call fuel budget: generous for real games, but an infinite
+/// loop is trapped in well under a second.
+pub const DEFAULT_FUEL: u64 = 200_000_000;
+
+/// Decodes a PNG (any color type, 8-bit after normalization) into a window icon.
+fn load_icon(path: &std::path::Path) -> Result<winit::window::Icon> {
+    let mut decoder = png::Decoder::new(fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    buf.truncate(info.buffer_size());
+
+    let rgba: Vec<u8> = match info.color_type.samples() {
+        1 => buf.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        2 => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        3 => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        _ => buf,
+    };
+    Ok(winit::window::Icon::from_rgba(rgba, info.width, info.height)?)
 }
 
-pub fn run(cart: Cartridge) -> Result<()> {
+/// Quiet period after the last change to the .wasm (or manifest) before
+/// reloading it, so a file that is still being written is never read.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Watches the directory holding `path` (builds and editors often replace the
+/// file by renaming over it, which would end a watch on the file itself) and
+/// sends one message per change to that file.
+fn watch_file(path: &std::path::Path) -> Result<(notify::RecommendedWatcher, mpsc::Receiver<()>)> {
+    use notify::{RecursiveMode, Watcher};
+
+    let name = path.file_name().context("path has no file name")?.to_owned();
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => std::path::PathBuf::from("."),
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let std::result::Result::Ok(ev) = res {
+            if !ev.kind.is_access() && ev.paths.iter().any(|p| p.file_name() == Some(name.as_os_str())) {
+                let _ = tx.send(());
+            }
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    Ok((watcher, rx))
+}
+
+/// How long a mute/solo or palette change stays in the window title.
+const NOTE_TIME: Duration = Duration::from_secs(3);
+
+/// `1 2M 3S 4` style summary of the channel debug flags.
+fn channel_status(muted: &[bool; 4], soloed: &[bool; 4]) -> String {
+    (0..4)
+        .map(|i| format!("{}{}{}", i + 1, if muted[i] { "M" } else { "" }, if soloed[i] { "S" } else { "" }))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Window cursor position (physical px) → framebuffer pixel, clamped to the
+/// framebuffer so positions over the letterbox bars land on the nearest edge.
+fn cursor_to_fb(pixels: &Pixels, scale_mode: ScaleMode, pos: (f32, f32), fb: (u32, u32), surface: (u32, u32)) -> (u32, u32) {
+    match scale_mode {
+        ScaleMode::Integer => {
+            let (x, y) = pixels.window_pos_to_pixel(pos).unwrap_or_else(|p| pixels.clamp_pixel_pos(p));
+            (x as u32, y as u32)
+        }
+        // the image covers the whole surface
+        ScaleMode::Stretch => {
+            let axis = |p: f32, s: u32, n: u32| ((p / s.max(1) as f32 * n as f32) as u32).min(n - 1);
+            (axis(pos.0, surface.0, fb.0), axis(pos.1, surface.1, fb.1))
+        }
+    }
+}
+
+pub fn run(mut cart: Cartridge) -> Result<()> {
     const FRAME_TIME: Duration = Duration::from_micros(16_667); // ~60 Hz
 
     // Event loop
@@ -282,12 +1652,21 @@ pub fn run(cart: Cartridge) -> Result<()> {
     let win_h = cart.h * cart.scale;
 
     let window = WindowBuilder::new()
-        .with_title("OxidoBoy")
+        .with_title(&cart.title)
         .with_inner_size(LogicalSize::new(win_w as f64, win_h as f64))
         // window doesn't resize below framebuffer size
         .with_min_inner_size(LogicalSize::new(cart.w as f64, cart.h as f64))
+        .with_decorations(!cart.borderless)
+        .with_window_level(if cart.on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal })
         .build(&event_loop)?;
 
+    if let Some(path) = &cart.icon {
+        match load_icon(path) {
+            std::result::Result::Ok(icon) => window.set_window_icon(Some(icon)),
+            Err(e) => eprintln!("⚠️  OxidoBoy: icon {} ignored: {e}", path.display()),
+        }
+    }
+
     let size = window.inner_size();
 
     // pixels
@@ -296,70 +1675,107 @@ pub fn run(cart: Cartridge) -> Result<()> {
         cart.h,
         SurfaceTexture::new(size.width, size.height, &window),
     )?;
+    let mut post = PostProcess::new(&pixels, cart.filter, cart.scale_mode, (size.width, size.height));
+    apply_background(&mut pixels, post.as_mut(), cart.bg);
+
+    // Audio (before the guest, so oxido_init can already play SFX)
+    let rec_ring = cart.record_audio.as_ref().map(|_| Arc::new(SampleRing::new(RING_SAMPLES)));
+    let audio_engine = AudioEngine::with_config(cart.audio_device.as_deref(), cart.sample_rate, rec_ring.clone());
+    let mut recorder = match (&cart.record_audio, rec_ring, &audio_engine) {
+        (Some(path), Some(ring), Some(eng)) => Some(Recorder::start(path.clone(), ring, eng.sample_rate as u32)?),
+        (Some(_), _, None) => { eprintln!("⚠️  OxidoBoy: no audio output, so nothing to record"); None }
+        _ => None,
+    };
+    if let Some(eng) = &audio_engine {
+        if let Some(e) = cart.echo { eng.set_delay(e.time_ms, e.feedback, e.mix); }
+        eng.set_lowpass(cart.lowpass_hz);
+        if let Some(c) = cart.crush { eng.set_crush(c.bits, c.rate_div); }
+    }
+    let sounds = load_sfx(&cart.sfx, audio_engine.as_ref().map(|e| e.sample_rate as u32))?;
 
     // WASM setup
-    let engine = Engine::default();
-
-    fn instantiate_all(
-        engine: &Engine,
-        wasm_path: &std::path::Path,
-    ) -> Result<(
-        Store<()>,
-        Instance,
-        Memory,
-        TypedFunc<(), ()>,     // init
-        TypedFunc<f32, ()>,    // update
-        TypedFunc<(), u32>,    // draw_ptr
-        TypedFunc<(), u32>,    // draw_len
-        TypedFunc<u32, ()>,    // input_set
-        Option<TypedFunc<(), u32>>, // audio_state_ptr
-        Option<TypedFunc<(), u32>>, // audio_state_len (bytes)
-    )> {
-        let module = Module::from_file(engine, wasm_path)?;
-        let linker = Linker::new(engine);
-        let mut store = Store::new(engine, ());
-        let instance = linker.instantiate(&mut store, &module)?;
-
-        let memory   = instance.get_memory(&mut store, "memory").context("no memory export")?;
-        let init     = instance.get_typed_func::<(), ()>(&mut store, "oxido_init").context("missing oxido_init")?;
-        let update   = instance.get_typed_func::<f32, ()>(&mut store, "oxido_update").context("missing oxido_update")?;
-        let draw_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_draw_ptr").context("missing oxido_draw_ptr")?;
-        let draw_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_draw_len").context("missing oxido_draw_len")?;
-        let input_set= instance.get_typed_func::<u32, ()>(&mut store, "oxido_input_set").context("missing oxido_input_set")?;
-
-        let audio_ptr = instance.get_typed_func::<(), u32>(&mut store, "oxido_audio_state_ptr").ok();
-        let audio_len = instance.get_typed_func::<(), u32>(&mut store, "oxido_audio_state_len").ok();
-
-        Ok((store, instance, memory, init, update, draw_ptr, draw_len, input_set, audio_ptr, audio_len))
-    }
-
-    let (mut store, mut _instance, mut memory, mut init, mut update, mut draw_ptr, mut draw_len, mut input_set, mut audio_ptr_fn, mut audio_len_fn)
-        = instantiate_all(&engine, &cart.wasm_path)?;
-    init.call(&mut store, ())?;
-
-    let mut last_mtime: SystemTime = fs::metadata(&cart.wasm_path)
-        .and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
-    let mut reload_count: u32 = 0;
+    let engine = make_engine(cart.fuel)?;
+    let start = Instant::now();
+    let mut host = HostState::new(cart.save_path.clone(), start);
+    host.sfx = audio_engine.as_ref().map(|e| SfxBank { sounds: Arc::new(sounds), mixer: e.shared.clone() });
+    host.audio_clock = audio_engine.as_ref().map(AudioEngine::clock);
+    if let Some(dir) = &cart.assets_dir { host.assets = Arc::new(load_assets(dir)); }
+    let mut guest = instantiate_all(&engine, &cart.wasm_path, host.clone())?;
+    guest.refuel(cart.fuel);
+    guest.init.call(&mut guest.store, ())?;
+    // previous instance kept around so a runaway reload can be reverted
+    let mut last_good: Option<Guest> = None;
+    // set when the guest traps: the frame freezes until a reload fixes it
+    let mut trap_msg: Option<String> = None;
+    let mut bad_draw_warned = false;
+    // next copy ignores `oxido_dirty_rect` (first frame, after reloads and traps)
+    let mut full_copy = true;
+    // active `oxido_flash`: color, duration, start
+    let mut flash: Option<(u32, Duration, Instant)> = None;
 
-    // Audio
-    let audio_engine = AudioEngine::new();
+    // `run` never returns, so the watcher lives as long as the loop
+    let (_watcher, reload_rx) = match watch_file(&cart.wasm_path) {
+        std::result::Result::Ok((w, rx)) => (Some(w), Some(rx)),
+        Err(e) => {
+            eprintln!("⚠️  OxidoBoy: can't watch {} ({e}); hot-reload disabled", cart.wasm_path.display());
+            (None, None)
+        }
+    };
+    // time of the latest change not yet reloaded
+    let mut pending_change: Option<Instant> = None;
+    // same for the manifest; `live` is what it said last time
+    let manifest = cart.manifest.take();
+    let (_manifest_watcher, manifest_rx) = match manifest.as_ref().map(|m| watch_file(&m.path)) {
+        Some(std::result::Result::Ok((w, rx))) => (Some(w), Some(rx)),
+        Some(Err(e)) => {
+            eprintln!("⚠️  OxidoBoy: can't watch manifest.toml ({e}); changes need a restart");
+            (None, None)
+        }
+        None => (None, None),
+    };
+    let mut live = manifest.as_ref().and_then(|m| (m.parse)(&fs::read_to_string(&m.path).ok()?).ok());
+    let mut pending_manifest: Option<Instant> = None;
+    let mut reload_count: u32 = 0;
 
     // Input
     let mut input_bits: u32 = 0;
-    fn bit_from_scancode(sc: u32) -> u32 {
-        match sc {
-            103 => 1 << 0, 108 => 1 << 1, 105 => 1 << 2, 106 => 1 << 3,
-            44  => 1 << 4, 45  => 1 << 5, 28  => 1 << 6, 42 | 54 => 1 << 7,
-            _ => 0,
-        }
-    }
+    let mut cursor: (f32, f32) = (0.0, 0.0); // physical window px
+    let mut mouse_buttons: u32 = 0;
+    let mut modifiers = ModifiersState::empty();
+
+    // Channel debugging: Ctrl+1..4 mutes, Alt+1..4 solos
+    let mut muted = [false; 4];
+    let mut soloed = [false; 4];
+    // shown in the title for a few seconds after a change
+    let mut title_note: Option<(String, Instant)> = None;
+    // F3: per-channel meters over the game image
+    let mut show_meters = false;
 
     // Overlay + pacing
-    let mut last = Instant::now();
+    let mut last = start;
     let mut fps_timer = Instant::now();
     let mut frames: u32 = 0;
+    // frames the game was updated in, for `oxido_frame_count` (not reset like `frames`)
+    let mut frame_total: u64 = 0;
     let mut ms_accum: f32 = 0.0;
     let mut next_frame = Instant::now();
+    // F1: FPS/ms in the framebuffer (last one-second average), F2: frame-time graph
+    let mut show_fps = false;
+    let mut show_graph = false;
+    let mut shown_fps: (f32, f32) = (0.0, 0.0);
+    let mut frame_times: VecDeque<f32> = VecDeque::with_capacity(FRAME_GRAPH_LEN);
+    // game time not yet consumed by fixed steps (`--fixed-dt`)
+    let mut dt_acc: f32 = 0.0;
+    // --rewind-seconds: snapshot ring, and whether Backspace is held
+    let mut rewind = (cart.rewind_seconds > 0).then(|| Rewind::new(cart.rewind_seconds));
+    if let Some(r) = &rewind {
+        eprintln!("⏪ OxidoBoy: rewind keeps {} s (~{} MiB at the current memory size); hold Backspace",
+            cart.rewind_seconds, r.budget(&guest).div_ceil(1 << 20));
+    }
+    let mut rewinding = false;
+    let speed = if cart.speed.is_finite() { cart.speed.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end()) } else { 1.0 };
+    // --profile: oxido_update and oxido_draw_ptr timings
+    let mut profile = cart.profile.then(|| (CallStats::default(), CallStats::default()));
 
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::WaitUntil(next_frame);
@@ -369,31 +1785,80 @@ pub fn run(cart: Cartridge) -> Result<()> {
                 WindowEvent::Resized(new_size) => {
                     // notifies pixels of the new surface size
                     let _ = pixels.resize_surface(new_size.width, new_size.height);
+                    if let Some(pp) = post.as_mut() { pp.resize(new_size.width, new_size.height); }
                 }
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                     // HiDPI: comes as &mut; take copy and resize
                     let size = *new_inner_size;
                     let _ = pixels.resize_surface(size.width, size.height);
+                    if let Some(pp) = post.as_mut() { pp.resize(size.width, size.height); }
                 }
+                WindowEvent::ModifiersChanged(m) => modifiers = m,
                 WindowEvent::KeyboardInput { input, .. } => {
                     let pressed = input.state == ElementState::Pressed;
-                    let mut bit = match input.virtual_keycode {
-                        Some(VirtualKeyCode::Up)    => 1 << 0,
-                        Some(VirtualKeyCode::Down)  => 1 << 1,
-                        Some(VirtualKeyCode::Left)  => 1 << 2,
-                        Some(VirtualKeyCode::Right) => 1 << 3,
-                        Some(VirtualKeyCode::Z)     => 1 << 4,
-                        Some(VirtualKeyCode::X)     => 1 << 5,
-                        Some(VirtualKeyCode::Return)=> 1 << 6,
-                        Some(VirtualKeyCode::LShift)| Some(VirtualKeyCode::RShift) => 1 << 7,
-                        _ => 0,
+                    let toggle = match (pressed, input.virtual_keycode) {
+                        (true, Some(VirtualKeyCode::F1)) => Some(&mut show_fps),
+                        (true, Some(VirtualKeyCode::F2)) => Some(&mut show_graph),
+                        (true, Some(VirtualKeyCode::F3)) => Some(&mut show_meters),
+                        _ => None,
                     };
-                    if bit == 0 { bit = bit_from_scancode(input.scancode); }
+                    if pressed && input.virtual_keycode == Some(VirtualKeyCode::F4) {
+                        cart.palette = PaletteOverride::cycle(cart.palette);
+                        let note = format!("palette: {}", cart.palette.map_or("off", |p| p.name()));
+                        window.set_title(&format!("{} — {note}", cart.title));
+                        title_note = Some((note, Instant::now()));
+                        full_copy = true;
+                    }
+                    if input.virtual_keycode == Some(VirtualKeyCode::Back) && rewind.is_some() && rewinding != pressed {
+                        rewinding = pressed;
+                        if pressed {
+                            window.set_title(&format!("{} — rewind", cart.title));
+                            title_note = Some(("rewind".to_string(), Instant::now()));
+                        }
+                    }
+                    if let Some(flag) = toggle {
+                        *flag = !*flag;
+                        // repaint the area under the overlay when it goes away
+                        full_copy = true;
+                    }
+                    let channel = match input.virtual_keycode {
+                        Some(VirtualKeyCode::Key1) => Some(0),
+                        Some(VirtualKeyCode::Key2) => Some(1),
+                        Some(VirtualKeyCode::Key3) => Some(2),
+                        Some(VirtualKeyCode::Key4) => Some(3),
+                        _ => None,
+                    };
+                    if let (Some(ch), true, Some(eng)) = (channel, pressed, audio_engine.as_ref()) {
+                        if modifiers.ctrl() || modifiers.alt() {
+                            if modifiers.ctrl() { muted[ch] = !muted[ch]; eng.set_mute(ch, muted[ch]); }
+                            if modifiers.alt() { soloed[ch] = !soloed[ch]; eng.set_solo(ch, soloed[ch]); }
+                            let note = channel_status(&muted, &soloed);
+                            let note = format!("audio: {note}");
+                            window.set_title(&format!("{} — {note}", cart.title));
+                            title_note = Some((note, Instant::now()));
+                        }
+                    }
+                    let bit = input_bit(input.virtual_keycode);
                     if bit != 0 {
                         if pressed { input_bits |= bit; } else { input_bits &= !bit; }
                     }
                 }
-                WindowEvent::Focused(false) => { input_bits = 0; },
+                WindowEvent::CursorMoved { position, .. } => {
+                    cursor = (position.x as f32, position.y as f32);
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    let bit = match button {
+                        MouseButton::Left => 1 << 0,
+                        MouseButton::Right => 1 << 1,
+                        MouseButton::Middle => 1 << 2,
+                        _ => 0,
+                    };
+                    if state == ElementState::Pressed { mouse_buttons |= bit; } else { mouse_buttons &= !bit; }
+                }
+                WindowEvent::Focused(focused) => {
+                    if !focused { input_bits = 0; mouse_buttons = 0; rewinding = false; }
+                    if let (false, Some(eng)) = (cart.background_audio, audio_engine.as_ref()) { eng.set_master_mute(!focused); }
+                }
                 _ => {}
             },
 
@@ -404,90 +1869,324 @@ pub fn run(cart: Cartridge) -> Result<()> {
                 last = now;
                 frames += 1;
                 ms_accum += dt_ms;
+                if frame_times.len() == FRAME_GRAPH_LEN { frame_times.pop_front(); }
+                frame_times.push_back(dt_ms);
+                let mut guest_exit = false;
 
-                // Hot-reload
-                match fs::metadata(&cart.wasm_path) {
-                    std::result::Result::Ok(meta) => match meta.modified() {
-                        std::result::Result::Ok(mod_time) => {
-                            if mod_time > last_mtime {
-                                match instantiate_all(&engine, &cart.wasm_path) {
-                                    std::result::Result::Ok((s, i, mem, ini, upd, dptr, dlen, iset, ap, al)) => {
-                                        store = s; _instance = i; memory = mem;
-                                        init = ini; update = upd; draw_ptr = dptr; draw_len = dlen; input_set = iset;
-                                        audio_ptr_fn = ap; audio_len_fn = al;
-                                        let _ = init.call(&mut store, ());
-                                        last_mtime = mod_time;
-                                        reload_count += 1;
-                                        eprintln!("🔁 OxidoBoy: reloaded {}", cart.wasm_path.display());
-                                    }
-                                    _ => eprintln!("⚠️  OxidoBoy: reload failed; keeping the previous version"),
+                // Hot-reload (debounced: every new event restarts the quiet period)
+                if let Some(rx) = &reload_rx {
+                    while rx.try_recv().is_ok() { pending_change = Some(now); }
+                }
+                if pending_change.is_some_and(|t| now - t >= RELOAD_DEBOUNCE) {
+                    pending_change = None;
+                    match instantiate_all(&engine, &cart.wasm_path, host.clone()) {
+                        std::result::Result::Ok(mut g) => {
+                            // carry the game state over when both versions speak the
+                            // serialize ABI; otherwise start fresh
+                            guest.refuel(cart.fuel);
+                            let state = if g.deserialize.is_some() { guest.snapshot_state() } else { None };
+                            g.refuel(cart.fuel);
+                            let restored = match state {
+                                Some(bytes) => match g.restore_state(&bytes) {
+                                    std::result::Result::Ok(()) => true,
+                                    Err(e) => { eprintln!("⚠️  OxidoBoy: state restore failed ({e}); starting fresh"); false }
+                                },
+                                None => false,
+                            };
+                            if !restored {
+                                g.refuel(cart.fuel);
+                                let _ = g.init.call(&mut g.store, ());
+                            }
+                            last_good = Some(std::mem::replace(&mut guest, g));
+                            // snapshots of the old build don't fit the new one
+                            if let Some(r) = rewind.as_mut() { r.snaps.clear(); }
+                            trap_msg = None;
+                            bad_draw_warned = false;
+                            full_copy = true;
+                            reload_count += 1;
+                            eprintln!("🔁 OxidoBoy: reloaded {}{}", cart.wasm_path.display(),
+                                if restored { " (state kept)" } else { "" });
+                        }
+                        _ => eprintln!("⚠️  OxidoBoy: reload failed; keeping the previous version"),
+                    }
+                }
+
+                // Manifest: presentation settings apply live
+                if let Some(rx) = &manifest_rx {
+                    while rx.try_recv().is_ok() { pending_manifest = Some(now); }
+                }
+                if let (Some(t), Some(m)) = (pending_manifest, &manifest) {
+                    if now - t >= RELOAD_DEBOUNCE {
+                        pending_manifest = None;
+                        match fs::read_to_string(&m.path).map_err(Error::from).and_then(|src| (m.parse)(&src)) {
+                            std::result::Result::Ok(new) => {
+                                if live.as_ref().is_some_and(|l| (l.w, l.h) != (new.w, new.h)) {
+                                    eprintln!("⚠️  OxidoBoy: manifest width/height changed; restart to apply");
+                                }
+                                if new.scale != cart.scale {
+                                    cart.scale = new.scale;
+                                    // the Resized event that follows resizes the surface
+                                    window.set_inner_size(LogicalSize::new((cart.w * cart.scale) as f64, (cart.h * cart.scale) as f64));
+                                }
+                                if new.title != cart.title {
+                                    cart.title = new.title.clone();
+                                    window.set_title(&cart.title);
+                                }
+                                if new.filter != cart.filter {
+                                    cart.filter = new.filter;
+                                    post = PostProcess::new(&pixels, cart.filter, cart.scale_mode, window.inner_size().into());
+                                    apply_background(&mut pixels, post.as_mut(), cart.bg);
+                                    full_copy = true;
+                                }
+                                if new.bg != cart.bg {
+                                    cart.bg = new.bg;
+                                    apply_background(&mut pixels, post.as_mut(), cart.bg);
                                 }
+                                live = Some(new);
+                                eprintln!("🔁 OxidoBoy: applied manifest.toml (scale {}, filter {:?})", cart.scale, cart.filter);
                             }
+                            Err(e) => eprintln!("⚠️  OxidoBoy: manifest.toml not applied: {e}"),
                         }
-                        _ => {}
-                    },
-                    _ => {}
+                    }
                 }
 
-                // input + update
-                let _ = input_set.call(&mut store, input_bits);
-                let _ = update.call(&mut store, dt_ms);
+                // input + update (skipped while frozen on a trap)
+                if trap_msg.is_none() {
+                    let mut fault: Option<Error> = None;
+                    guest.refuel(cart.fuel);
+                    let _ = guest.input_set.call(&mut guest.store, input_bits);
+                    let mouse_pos = cursor_to_fb(&pixels, cart.scale_mode, cursor, (cart.w, cart.h), window.inner_size().into());
+                    let host = guest.store.data_mut();
+                    host.mouse_pos = mouse_pos;
+                    host.mouse_buttons = mouse_buttons;
+                    host.frame_count = frame_total;
+                    host.uptime_ms = (now - start).as_secs_f64() * 1000.0;
+                    if !rewinding { frame_total += 1; }
+                    let game_dt = (if cart.max_dt_ms > 0.0 { dt_ms.min(cart.max_dt_ms) } else { dt_ms }) * speed;
+                    // variable dt: one update per frame; fixed dt: as many fixed
+                    // steps as the accumulated time covers (possibly none)
+                    let (steps, step_dt) = match cart.fixed_dt_ms {
+                        Some(fixed) => {
+                            dt_acc += game_dt;
+                            let n = ((dt_acc / fixed) as u32).min(MAX_FIXED_STEPS);
+                            // past the cap the backlog is dropped, not carried over
+                            dt_acc = if n == MAX_FIXED_STEPS { dt_acc % fixed } else { dt_acc - n as f32 * fixed };
+                            (n, fixed)
+                        }
+                        None => (1, game_dt),
+                    };
+                    match rewind.as_mut() {
+                        // rewinding replaces the update: older state, same draw path
+                        Some(r) if rewinding => {
+                            if let Some(size) = r.step_back(&mut guest) {
+                                guest.store.data_mut().resize_request = Some(size);
+                                full_copy = true;
+                            }
+                        }
+                        _ => {
+                            for i in 0..steps {
+                                if i > 0 { guest.refuel(cart.fuel); }
+                                let t0 = profile.is_some().then(Instant::now);
+                                let res = guest.update.call(&mut guest.store, step_dt);
+                                if let (Some((u, _)), Some(t0)) = (profile.as_mut(), t0) { u.record(t0.elapsed()); }
+                                if let Err(e) = res { fault = Some(e); break; }
+                            }
+                            if fault.is_none() && guest.wants_exit(cart.fuel) { guest_exit = true; }
+                        }
+                    }
 
-                // video
-                let ptr = draw_ptr.call(&mut store, ()).unwrap() as usize;
-                let len = draw_len.call(&mut store, ()).unwrap() as usize;
-                let data = memory.data(&store);
-                let frame = pixels.frame_mut();
-                frame.copy_from_slice(&data[ptr..ptr + len]);
+                    // framebuffer size change asked for during init/update
+                    if let Some((w, h)) = guest.store.data_mut().resize_request.take() {
+                        if (w, h) != (cart.w, cart.h) {
+                            match pixels.resize_buffer(w, h) {
+                                std::result::Result::Ok(()) => {
+                                    cart.w = w;
+                                    cart.h = h;
+                                    if let Some(pp) = post.as_mut() { pp.rebind(&pixels); }
+                                    window.set_min_inner_size(Some(LogicalSize::new(w as f64, h as f64)));
+                                    full_copy = true;
+                                    bad_draw_warned = false;
+                                }
+                                Err(e) => eprintln!("⚠️  OxidoBoy: can't resize the framebuffer to {w}x{h}: {e}"),
+                            }
+                        }
+                    }
+
+                    // after the resize, so the snapshot's size matches its memory
+                    if let (Some(r), false, None) = (rewind.as_mut(), rewinding, &fault) {
+                        r.record(&guest, (cart.w, cart.h));
+                    }
+
+                    // video
+                    let mut draw = None;
+                    if fault.is_none() {
+                        guest.refuel(cart.fuel);
+                        let t0 = profile.is_some().then(Instant::now);
+                        let drawn = guest.draw_ptr.call(&mut guest.store, ());
+                        if let (Some((_, d)), Some(t0)) = (profile.as_mut(), t0) { d.record(t0.elapsed()); }
+                        match drawn.and_then(|ptr| Ok((ptr, guest.draw_len.call(&mut guest.store, ())?))) {
+                            std::result::Result::Ok((ptr, len)) => {
+                                let full = (0, 0, cart.w as usize, cart.h as usize);
+                                let rect = match &guest.dirty_rect {
+                                    Some(f) if !full_copy => f.call(&mut guest.store, ())
+                                        .map(|v| clip_dirty_rect(v, cart.w, cart.h))
+                                        .unwrap_or(full),
+                                    _ => full,
+                                };
+                                draw = Some((ptr as usize, len as usize, rect));
+                            }
+                            Err(e) => fault = Some(e),
+                        }
+                    }
+                    if let Some(e) = fault {
+                        if is_out_of_fuel(&e) {
+                            eprintln!("⏱️  OxidoBoy: guest call exceeded its fuel budget ({} units)", cart.fuel);
+                            match last_good.take() {
+                                Some(prev) => {
+                                    guest = prev;
+                                    if let Some(r) = rewind.as_mut() { r.snaps.clear(); }
+                                    full_copy = true;
+                                    eprintln!("⚠️  OxidoBoy: reverted to the previous version");
+                                }
+                                None => trap_msg = Some("out of fuel (infinite loop?)".to_string()),
+                            }
+                        } else {
+                            eprintln!("💥 OxidoBoy: guest trapped: {e:?}");
+                            trap_msg = Some(e.root_cause().to_string());
+                        }
+                    }
+                    if let Some((ptr, len, (x, y, w, h))) = draw {
+                        // memory may have grown (or the guest reported garbage): check the
+                        // range against the current size instead of trusting it
+                        let frame = pixels.frame_mut();
+                        match guest_slice(&guest.memory, &guest.store, ptr, len) {
+                            Some(src) if src.len() == frame.len() => {
+                                // only the dirty rows/columns (the whole frame by default)
+                                copy_frame_rect(src, frame, cart.w, (x, y, w, h), cart.filter == Filter::Dmg);
+                                full_copy = false;
+                            }
+                            _ if !bad_draw_warned => {
+                                eprintln!(
+                                    "⚠️  OxidoBoy: draw range {ptr}+{len} doesn't fit guest memory ({} bytes) or the {} byte framebuffer; skipping copy",
+                                    guest.memory.data_size(&guest.store), frame.len()
+                                );
+                                bad_draw_warned = true;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                // accessibility palette over the game image (not the overlays);
+                // the remap is per frame, so every frame is copied in full
+                if let Some(p) = cart.palette {
+                    remap_palette(pixels.frame_mut(), &p.palette());
+                    full_copy = true;
+                }
+                // screen flash (fading linearly; a new call restarts it)
+                if let Some((color, ms)) = guest.store.data_mut().flash_request.take() {
+                    flash = (ms > 0).then(|| (color, Duration::from_millis(ms as u64), Instant::now()));
+                    full_copy = true;
+                }
+                if let Some((color, dur, t0)) = flash {
+                    let left = 1.0 - t0.elapsed().as_secs_f32() / dur.as_secs_f32();
+                    if left > 0.0 { apply_flash(pixels.frame_mut(), color, left); } else { flash = None; }
+                    full_copy = true;
+                }
+                // keep the last good image and paint the trap over it
+                if let Some(msg) = &trap_msg {
+                    draw_trap_banner(pixels.frame_mut(), cart.w, cart.h, msg);
+                    full_copy = true;
+                }
+                if let (true, Some(eng)) = (show_meters, audio_engine.as_ref()) {
+                    draw_audio_meters(pixels.frame_mut(), cart.w, cart.h, &eng.snapshot(), &muted, &soloed);
+                    full_copy = true;
+                }
+                if show_fps || show_graph {
+                    let mut lines = Vec::new();
+                    if show_fps {
+                        lines.push(format!("{:.0} FPS {:.1} MS", shown_fps.0, shown_fps.1));
+                        if let Some((u, d)) = &profile {
+                            lines.push(format!("UPD {:.2} DRW {:.2}", u.avg_ms, d.avg_ms));
+                        }
+                    }
+                    let graph = show_graph.then_some(&frame_times);
+                    draw_fps_overlay(pixels.frame_mut(), cart.w, cart.h, &lines, graph);
+                    full_copy = true;
+                }
 
                 // === Audio: read game state and set parameters ===
-                if let (Some(ref ap), Some(ref al), Some(ref eng)) =
-                    (audio_ptr_fn.as_ref(), audio_len_fn.as_ref(), audio_engine.as_ref())
+                if let (Some(ap), Some(al), Some(eng)) =
+                    (guest.audio_ptr.as_ref(), guest.audio_len.as_ref(), audio_engine.as_ref())
                 {
                     if let (std::result::Result::Ok(ptr_u32), std::result::Result::Ok(len_u32)) =
-                        (ap.call(&mut store, ()), al.call(&mut store, ()))
+                        (ap.call(&mut guest.store, ()), al.call(&mut guest.store, ()))
                     {
                         let ptr = ptr_u32 as usize;
                         let blen = len_u32 as usize;
 
-                        // 4 channels * 13 fields * 4 bytes
-                        if blen >= 4 * 13 * 4 {
-                            let slice = &memory.data(&store)[ptr..ptr + blen];
+                        // 4 channels * WIRE_FIELDS_MIN..=WIRE_FIELDS fields * 4 bytes;
+                        // the channel stride follows the length the cart reports
+                        let slice = guest_slice(&guest.memory, &guest.store, ptr, blen);
+                        if let Some(slice) = slice.filter(|s| s.len() >= 4 * WIRE_FIELDS_MIN * 4) {
                             let mut chans = [WireCh::default(); 4];
-                            let mut off = 0usize;
-                            for i in 0..4 {
+                            let stride = slice.len() / 4 / 4 * 4;
+                            let fields = (stride / 4).min(WIRE_FIELDS);
+                            for (i, ch) in chans.iter_mut().enumerate() {
+                                let mut off = i * stride;
+                                let end = off + fields * 4;
                                 let rd_u32 = |s: &[u8], o: &mut usize| { let v = u32::from_le_bytes(s[*o..*o+4].try_into().unwrap()); *o+=4; v };
                                 let rd_f32 = |s: &[u8], o: &mut usize| { let v = f32::from_le_bytes(s[*o..*o+4].try_into().unwrap()); *o+=4; v };
                                 let rd_i32 = |s: &[u8], o: &mut usize| { let v = i32::from_le_bytes(s[*o..*o+4].try_into().unwrap()); *o+=4; v };
 
-                                chans[i].kind        = rd_u32(slice, &mut off);
-                                chans[i].base_freq   = rd_f32(slice, &mut off);
-                                chans[i].vol         = rd_f32(slice, &mut off);
-                                chans[i].duty        = rd_f32(slice, &mut off);
-                                chans[i].gate        = rd_u32(slice, &mut off);
-
-                                chans[i].a_ms        = rd_f32(slice, &mut off);
-                                chans[i].d_ms        = rd_f32(slice, &mut off);
-                                chans[i].s_lvl       = rd_f32(slice, &mut off);
-                                chans[i].r_ms        = rd_f32(slice, &mut off);
-
-                                chans[i].arp_a       = rd_i32(slice, &mut off);
-                                chans[i].arp_b       = rd_i32(slice, &mut off);
-                                chans[i].arp_c       = rd_i32(slice, &mut off);
-                                chans[i].arp_rate_hz = rd_f32(slice, &mut off);
+                                ch.kind        = rd_u32(slice, &mut off);
+                                ch.base_freq   = rd_f32(slice, &mut off);
+                                ch.vol         = rd_f32(slice, &mut off);
+                                ch.duty        = rd_f32(slice, &mut off);
+                                ch.gate        = rd_u32(slice, &mut off);
+
+                                ch.a_ms        = rd_f32(slice, &mut off);
+                                ch.d_ms        = rd_f32(slice, &mut off);
+                                ch.s_lvl       = rd_f32(slice, &mut off);
+                                ch.r_ms        = rd_f32(slice, &mut off);
+
+                                ch.arp_a       = rd_i32(slice, &mut off);
+                                ch.arp_b       = rd_i32(slice, &mut off);
+                                ch.arp_c       = rd_i32(slice, &mut off);
+                                ch.arp_rate_hz = rd_f32(slice, &mut off);
+
+                                if off < end { ch.env_curve = rd_u32(slice, &mut off); }
+                                if off < end { ch.portamento_ms = rd_f32(slice, &mut off); }
+                                if off < end { ch.noise_width = rd_u32(slice, &mut off); }
                             }
                             eng.set_params(&chans);
                         }
                     }
                 }
+                if let (Some(wp), Some(wl), Some(eng)) =
+                    (guest.wave_ptr.as_ref(), guest.wave_len.as_ref(), audio_engine.as_ref())
+                {
+                    if let (std::result::Result::Ok(ptr), std::result::Result::Ok(len)) =
+                        (wp.call(&mut guest.store, ()), wl.call(&mut guest.store, ()))
+                    {
+                        if let Some(bytes) = guest_slice(&guest.memory, &guest.store, ptr as usize, len as usize) {
+                            eng.set_wave(bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+                        }
+                    }
+                }
 
                 // overlay
                 if fps_timer.elapsed().as_secs_f32() >= 1.0 {
                     let fps = frames as f32 / fps_timer.elapsed().as_secs_f32();
                     let avg_ms = if frames > 0 { ms_accum / frames as f32 } else { 0.0 };
+                    shown_fps = (fps, avg_ms);
+                    if title_note.as_ref().is_some_and(|(_, t)| t.elapsed() >= NOTE_TIME) { title_note = None; }
+                    let mut note = title_note.as_ref().map(|(n, _)| format!("  |  {n}")).unwrap_or_default();
+                    if let Some((u, d)) = &profile {
+                        note += &format!("  |  update {:.2} ms, draw {:.2} ms", u.avg_ms, d.avg_ms);
+                    }
                     window.set_title(&format!(
-                        "OxidoBoy — {:>4.0} FPS ({:.2} ms)  |  reloads: {}",
-                        fps, avg_ms, reload_count
+                        "{} — {:>4.0} FPS ({:.2} ms)  |  reloads: {}{}",
+                        cart.title, fps, avg_ms, reload_count, note
                     ));
                     fps_timer = Instant::now();
                     frames = 0;
@@ -497,9 +2196,27 @@ pub fn run(cart: Cartridge) -> Result<()> {
                 window.request_redraw();
                 next_frame = Instant::now() + FRAME_TIME;
                 *control_flow = ControlFlow::WaitUntil(next_frame);
+                if guest_exit {
+                    eprintln!("👋 OxidoBoy: the game asked to exit");
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
+
+            Event::LoopDestroyed => {
+                drop(cart.unpacked.take());
+                if let Some(r) = recorder.take() { r.finish(); }
+                if let Some((u, d)) = &profile { print_profile(u, d); }
             }
 
-            Event::RedrawRequested(_) => { let _ = pixels.render(); }
+            Event::RedrawRequested(_) => {
+                let _ = match &post {
+                    Some(pp) => pixels.render_with(|encoder, target, ctx| {
+                        pp.render(encoder, target, ctx);
+                        std::result::Result::Ok(())
+                    }),
+                    None => pixels.render(),
+                };
+            }
             _ => {}
         }
     });
@@ -507,3 +2224,183 @@ pub fn run(cart: Cartridge) -> Result<()> {
     #[allow(unreachable_code)]
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads `wat` as a 2x2 cart (wasmtime parses the text format directly)
+    /// through a temp file that is removed once the module is instantiated.
+    fn headless(name: &str, wat: &str) -> Headless {
+        let path = std::env::temp_dir().join(format!("oxido-test-{}-{name}.wat", std::process::id()));
+        std::fs::write(&path, wat).unwrap();
+        let h = Headless::new(&Cartridge {
+            title: name.into(), wasm_path: path.clone(), w: 2, h: 2, scale: 1, save_path: None, fuel: 1_000_000,
+            filter: Filter::default(), scale_mode: ScaleMode::Integer, bg: Background::default(), icon: None,
+            audio_device: None, sample_rate: None, assets_dir: None, sfx: vec![], record_audio: None,
+            profile: false, background_audio: false, borderless: false, on_top: false, palette: None,
+            speed: 1.0, max_dt_ms: DEFAULT_MAX_DT_MS, fixed_dt_ms: None, echo: None, lowpass_hz: None,
+            crush: None, manifest: None, rewind_seconds: 0, unpacked: None,
+        });
+        let _ = std::fs::remove_file(path);
+        h.unwrap()
+    }
+
+    #[test]
+    fn draw_follows_framebuffer_moved_after_growth() {
+        // frame 0 draws from page 0; frame 1 grows memory and moves the
+        // framebuffer into the new page, like a game allocating post-init
+        let mut h = headless("grow", r#"(module
+          (memory (export "memory") 1)
+          (global $fb (mut i32) (i32.const 0))
+          (global $n (mut i32) (i32.const 0))
+          (func (export "oxido_init") (memory.fill (i32.const 0) (i32.const 1) (i32.const 16)))
+          (func (export "oxido_update") (param f32)
+            (if (i32.eq (global.get $n) (i32.const 1))
+              (then
+                (global.set $fb (i32.mul (memory.grow (i32.const 2)) (i32.const 65536)))
+                (global.set $fb (i32.add (global.get $fb) (i32.const 65536)))
+                (memory.fill (global.get $fb) (i32.const 7) (i32.const 16))))
+            (global.set $n (i32.add (global.get $n) (i32.const 1))))
+          (func (export "oxido_input_set") (param i32))
+          (func (export "oxido_draw_ptr") (result i32) (global.get $fb))
+          (func (export "oxido_draw_len") (result i32) (i32.const 16)))"#);
+        h.update(0, 16.0).unwrap();
+        assert_eq!(h.draw().unwrap(), &[1; 16]);
+        h.update(0, 16.0).unwrap();
+        assert_eq!(h.draw().unwrap(), &[7; 16]);
+    }
+
+    #[test]
+    fn draw_range_past_memory_is_an_error() {
+        let mut h = headless("oob", r#"(module
+          (memory (export "memory") 1)
+          (func (export "oxido_init"))
+          (func (export "oxido_update") (param f32))
+          (func (export "oxido_input_set") (param i32))
+          (func (export "oxido_draw_ptr") (result i32) (i32.const 65530))
+          (func (export "oxido_draw_len") (result i32) (i32.const 16)))"#);
+        h.update(0, 16.0).unwrap();
+        assert!(h.draw().is_err());
+    }
+
+    #[test]
+    fn should_exit_follows_the_export() {
+        let cart = |name, exit_fn: &str| headless(name, &format!(r#"(module
+          (memory (export "memory") 1)
+          (global $n (mut i32) (i32.const 0))
+          (func (export "oxido_init"))
+          (func (export "oxido_update") (param f32) (global.set $n (i32.add (global.get $n) (i32.const 1))))
+          (func (export "oxido_input_set") (param i32))
+          (func (export "oxido_draw_ptr") (result i32) (i32.const 0))
+          (func (export "oxido_draw_len") (result i32) (i32.const 16))
+          {exit_fn})"#));
+        // asks to quit after the third update
+        let mut h = cart("exit", r#"(func (export "oxido_should_exit") (result i32) (i32.ge_u (global.get $n) (i32.const 3)))"#);
+        for _ in 0..2 {
+            h.update(0, 16.0).unwrap();
+            assert!(!h.should_exit());
+        }
+        h.update(0, 16.0).unwrap();
+        assert!(h.should_exit());
+        // missing, trapping, out of fuel or the wrong signature: keep running
+        for (name, exit_fn) in [
+            ("exit-none", ""),
+            ("exit-trap", r#"(func (export "oxido_should_exit") (result i32) unreachable)"#),
+            ("exit-fuel", r#"(func (export "oxido_should_exit") (result i32) (loop $l (br $l)) (i32.const 1))"#),
+            ("exit-sig", r#"(func (export "oxido_should_exit") (param i32) (result i32) (i32.const 1))"#),
+        ] {
+            let mut h = cart(name, exit_fn);
+            h.update(0, 16.0).unwrap();
+            assert!(!h.should_exit(), "{name}");
+            h.update(0, 16.0).unwrap();
+        }
+    }
+
+    #[test]
+    fn gate_edges_are_declicked() {
+        // DC "pulse" (duty 1) with zero attack/release: without the slew the
+        // output would jump straight between 0 and full level on each edge
+        let sr = 48_000.0;
+        let shared = Arc::new(Mutex::new(AudioShared { master_gain: 1.0, ..Default::default() }));
+        shared.lock().unwrap().chans[0] = HostCh { base_freq: 440.0, vol: 1.0, duty: 1.0, gate: true, s_lvl: 1.0, ..Default::default() };
+        let max_step = 0.25 * 1000.0 / (DECLICK_MS * sr) + 1e-6; // per frame, after headroom
+        let mut t = 0;
+        let mut prev = 0.0;
+        for gate in [true, false] {
+            shared.lock().unwrap().chans[0].gate = gate;
+            let mut out = vec![0.0; 2 * 256];
+            fill_buffer(&mut out, sr, &shared, &mut t);
+            for frame in out.chunks_exact(2) {
+                assert!((frame[0] - prev).abs() <= max_step, "{gate}: {prev} -> {}", frame[0]);
+                prev = frame[0];
+            }
+            // 256 frames is over 5 ms: the ramp has finished either way
+            assert_eq!(prev, if gate { 0.25 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn sfx_voices_keep_the_newest() {
+        let long: SfxData = Arc::new(vec![[0.1, 0.1]; 1000]);
+        let short: SfxData = Arc::new(vec![[0.1, 0.1]; 10]);
+        let voice = |data: &SfxData, seq| SfxVoice { data: data.clone(), pos: 0, seq };
+        let seqs = |a: &AudioShared| {
+            let mut s: Vec<u64> = a.sfx.iter().flatten().map(|v| v.seq).collect();
+            s.sort();
+            s
+        };
+        let shared = Arc::new(Mutex::new(AudioShared::default()));
+        {
+            let mut a = shared.lock().unwrap();
+            a.add_sfx(voice(&short, 1));
+            for seq in 2..=6 { a.add_sfx(voice(&long, seq)); }
+            assert_eq!(seqs(&a), [3, 4, 5, 6]);
+            a.add_sfx(voice(&long, 2)); // older than everything playing
+            assert_eq!(seqs(&a), [3, 4, 5, 6]);
+        }
+        // the callback hands its voices back behind ones started meanwhile
+        let mut a = shared.lock().unwrap();
+        let mut taken = std::mem::take(&mut a.sfx);
+        a.add_sfx(voice(&short, 7));
+        // the oldest one finished while mixing
+        taken.iter_mut().flatten().find(|v| v.seq == 3).unwrap().pos = 1000;
+        for v in taken.into_iter().flatten().filter(|v| v.pos < v.data.len()) { a.add_sfx(v); }
+        assert_eq!(seqs(&a), [4, 5, 6, 7]);
+        drop(a);
+        // finished voices free their slot
+        let mut out = vec![0.0; 2 * 16];
+        fill_buffer(&mut out, 48_000.0, &shared, &mut 0);
+        assert_eq!(seqs(&shared.lock().unwrap()), [4, 5, 6]);
+    }
+
+    #[test]
+    fn lfsr_periods() {
+        for (width, period) in [(15, 32767), (7, 127)] {
+            let start = 1;
+            let mut s = lfsr_step(start, width);
+            let mut n = 1;
+            while s != start {
+                s = lfsr_step(s, width);
+                n += 1;
+                assert!(n <= period, "{width} bits: no repeat within {period} steps");
+            }
+            assert_eq!(n, period, "{width} bits");
+        }
+        // anything that isn't 7 is the 15-bit register
+        assert_eq!(lfsr_step(0x7fff, 0), lfsr_step(0x7fff, 15));
+    }
+
+    #[test]
+    fn callback_hands_the_old_echo_buffer_back() {
+        let delay = |frames| Delay { buf: vec![[0.0; 2]; frames], pos: 0, feedback: 0.5, mix: 0.5 };
+        let shared = Arc::new(Mutex::new(AudioShared { delay: delay(100), new_delay: Some(delay(200)), ..Default::default() }));
+        let mut out = vec![0.0; 2 * 16];
+        fill_buffer(&mut out, 48_000.0, &shared, &mut 0);
+        let s = shared.lock().unwrap();
+        assert_eq!(s.delay.buf.len(), 200);
+        assert!(s.new_delay.is_none());
+        // not freed on the audio thread: it waits for the next `set_delay`
+        assert_eq!(s.old_delay.as_ref().map(|d| d.buf.len()), Some(100));
+    }
+}
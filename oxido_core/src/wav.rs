@@ -0,0 +1,141 @@
+//! Minimal WAV reader for cart sound effects: 16-bit PCM or 32-bit float,
+//! mono or stereo. Everything is converted to stereo f32 frames. Plus a
+//! 32-bit float writer for `--record-audio`.
+
+use anyhow::*;
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// Decoded sound as stereo frames in -1..=1.
+pub struct Wav {
+    pub sample_rate: u32,
+    pub frames: Vec<[f32; 2]>,
+}
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Parses a RIFF/WAVE file. Unsupported encodings (8/24-bit PCM, ADPCM,
+/// more than two channels...) are rejected with the reason.
+pub fn parse(bytes: &[u8]) -> Result<Wav> {
+    ensure!(bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE", "not a RIFF/WAVE file");
+    let u16_at = |b: &[u8], o: usize| u16::from_le_bytes([b[o], b[o + 1]]);
+    let u32_at = |b: &[u8], o: usize| u32::from_le_bytes([b[o], b[o + 1], b[o + 2], b[o + 3]]);
+
+    let mut fmt: Option<(u16, u16, u32, u16)> = None; // format, channels, rate, bits
+    let mut data: Option<&[u8]> = None;
+    let mut off = 12;
+    while off + 8 <= bytes.len() {
+        let id = &bytes[off..off + 4];
+        let len = u32_at(bytes, off + 4) as usize;
+        let body = &bytes[off + 8..(off + 8 + len).min(bytes.len())];
+        match id {
+            b"fmt " => {
+                ensure!(body.len() >= 16, "fmt chunk too short");
+                let mut format = u16_at(body, 0);
+                if format == FORMAT_EXTENSIBLE {
+                    // the real format is the first two bytes of the sub-format GUID
+                    ensure!(body.len() >= 26, "extensible fmt chunk too short");
+                    format = u16_at(body, 24);
+                }
+                fmt = Some((format, u16_at(body, 2), u32_at(body, 4), u16_at(body, 14)));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        off += 8 + len + (len & 1); // chunks are padded to even sizes
+    }
+
+    let (format, channels, sample_rate, bits) = fmt.context("no fmt chunk")?;
+    let data = data.context("no data chunk")?;
+    ensure!(channels == 1 || channels == 2, "{channels} channels (only mono or stereo)");
+    ensure!(sample_rate > 0, "sample rate is 0");
+
+    let samples: Vec<f32> = match (format, bits) {
+        (FORMAT_PCM, 16) => data.chunks_exact(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0).collect(),
+        (FORMAT_FLOAT, 32) => data.chunks_exact(4).map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]])).collect(),
+        (FORMAT_PCM, b) => bail!("{b}-bit PCM (use 16-bit PCM or 32-bit float)"),
+        (FORMAT_FLOAT, b) => bail!("{b}-bit float (use 16-bit PCM or 32-bit float)"),
+        (f, _) => bail!("unsupported WAV encoding {f:#x} (use 16-bit PCM or 32-bit float)"),
+    };
+    let frames = if channels == 1 {
+        samples.iter().map(|&s| [s, s]).collect()
+    } else {
+        samples.chunks_exact(2).map(|p| [p[0], p[1]]).collect()
+    };
+    Ok(Wav { sample_rate, frames })
+}
+
+/// Linear-interpolation resample to `rate` (good enough for short SFX).
+pub fn resample(frames: &[[f32; 2]], from: u32, to: u32) -> Vec<[f32; 2]> {
+    if from == to || frames.is_empty() { return frames.to_vec(); }
+    let n = (frames.len() as u64 * to as u64 / from as u64).max(1) as usize;
+    let ratio = from as f64 / to as f64;
+    (0..n)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let i0 = (pos as usize).min(frames.len() - 1);
+            let i1 = (i0 + 1).min(frames.len() - 1);
+            let t = (pos - i0 as f64) as f32;
+            let (a, b) = (frames[i0], frames[i1]);
+            [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+        })
+        .collect()
+}
+
+/// Streams interleaved 32-bit float samples to a WAV file; sizes in the
+/// header are filled in by `finish`.
+pub struct WavWriter {
+    out: BufWriter<File>,
+    channels: u16,
+    samples: u64,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let block = channels as u32 * 4;
+        out.write_all(b"RIFF\0\0\0\0WAVE")?;
+        // fmt (18 bytes: non-PCM formats carry a cbSize), fact, then data
+        out.write_all(b"fmt ")?;
+        out.write_all(&18u32.to_le_bytes())?;
+        out.write_all(&FORMAT_FLOAT.to_le_bytes())?;
+        out.write_all(&channels.to_le_bytes())?;
+        out.write_all(&sample_rate.to_le_bytes())?;
+        out.write_all(&(sample_rate * block).to_le_bytes())?;
+        out.write_all(&(block as u16).to_le_bytes())?;
+        out.write_all(&32u16.to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?;
+        out.write_all(b"fact\x04\0\0\0\0\0\0\0")?;
+        out.write_all(b"data\0\0\0\0")?;
+        Ok(Self { out, channels, samples: 0 })
+    }
+
+    pub fn write(&mut self, samples: &[f32]) -> Result<()> {
+        for s in samples {
+            self.out.write_all(&s.to_le_bytes())?;
+        }
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Patches the RIFF/fact/data sizes and flushes. Returns the frame count.
+    pub fn finish(mut self) -> Result<u64> {
+        const HEADER: u64 = 12 + 26 + 12 + 8; // RIFF+WAVE, fmt, fact, data header
+        let frames = self.samples / self.channels as u64;
+        let data_len = u32::try_from(self.samples * 4).context("recording exceeds the 4 GiB WAV limit")?;
+        let riff_len = (HEADER - 8) as u32 + data_len;
+        self.out.seek(SeekFrom::Start(4))?;
+        self.out.write_all(&riff_len.to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(12 + 26 + 8))?;
+        self.out.write_all(&(frames as u32).to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(HEADER - 4))?;
+        self.out.write_all(&data_len.to_le_bytes())?;
+        self.out.flush()?;
+        Ok(frames)
+    }
+}
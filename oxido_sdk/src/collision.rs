@@ -0,0 +1,291 @@
+//! Shape tests beyond tile AABB: box overlap, circle vs box and a swept box
+//! test so fast movers (bullets, dashing players) can't tunnel through walls.
+
+use crate::math::Vec2;
+
+/// Axis-aligned box: top-left corner plus size, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self { Self { x, y, w, h } }
+    #[inline] pub fn right(&self) -> f32 { self.x + self.w }
+    #[inline] pub fn bottom(&self) -> f32 { self.y + self.h }
+}
+
+/// `true` if the boxes share area; touching edges don't count.
+pub fn aabb_overlap(a: Rect, b: Rect) -> bool {
+    a.x < b.right() && b.x < a.right() && a.y < b.bottom() && b.y < a.bottom()
+}
+
+/// Circle (center, radius) against a box: nearest point on the box vs radius.
+pub fn circle_rect(cx: f32, cy: f32, r: f32, rx: f32, ry: f32, rw: f32, rh: f32) -> bool {
+    // max/min instead of clamp: a negative size must not panic
+    let nx = cx.max(rx).min(rx + rw);
+    let ny = cy.max(ry).min(ry + rh);
+    let (dx, dy) = (cx - nx, cy - ny);
+    dx * dx + dy * dy < r * r
+}
+
+/// Moves `moving` by `vel` (one step) against `fixed`. Returns the time of
+/// impact in `0..=1` (fraction of `vel`) and the contact normal on `fixed`,
+/// or `None` if they don't meet during the step. Boxes that already overlap
+/// report `(0.0, Vec2::ZERO)`. Like `aabb_overlap`, contact without shared
+/// area is a miss: sliding along an edge, or an exact corner graze (where
+/// entry and exit time coincide).
+pub fn swept_aabb(moving: Rect, vel: Vec2, fixed: Rect) -> Option<(f32, Vec2)> {
+    if aabb_overlap(moving, fixed) {
+        return Some((0.0, Vec2::ZERO));
+    }
+    // Minkowski sum: sweep the top-left corner as a ray against the grown box
+    let grown = Rect::new(fixed.x - moving.w, fixed.y - moving.h, fixed.w + moving.w, fixed.h + moving.h);
+
+    let axis = |origin: f32, v: f32, lo: f32, hi: f32| -> Option<(f32, f32)> {
+        if v == 0.0 {
+            // parallel: must already be strictly between the faces
+            return (origin > lo && origin < hi).then_some((f32::NEG_INFINITY, f32::INFINITY));
+        }
+        let (t1, t2) = ((lo - origin) / v, (hi - origin) / v);
+        Some((t1.min(t2), t1.max(t2)))
+    };
+    let (x_entry, x_exit) = axis(moving.x, vel.x, grown.x, grown.right())?;
+    let (y_entry, y_exit) = axis(moving.y, vel.y, grown.y, grown.bottom())?;
+
+    let entry = x_entry.max(y_entry);
+    let exit = x_exit.min(y_exit);
+    if entry >= exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    let normal = if x_entry > y_entry {
+        Vec2::new(-vel.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, -vel.y.signum())
+    };
+    Some((entry, normal))
+}
+
+// ====================== Tile shapes ======================
+
+/// Solid part of one tile. Slopes are 45° (corner to corner) and solid below
+/// the diagonal: `SlopeLeft` is high on the left (`\`), `SlopeRight` high on
+/// the right (`/`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CollisionShape {
+    #[default]
+    Empty,
+    Full,
+    /// Upper half solid (a ledge you can stand on and bump your head under).
+    TopHalf,
+    SlopeLeft,
+    SlopeRight,
+    /// Platform whose top edge only stops things falling onto it from above.
+    OneWay,
+}
+
+/// Collision layer laid out like a `TileMap`: `shapes[r * w + c]`.
+/// Everything outside the map is empty.
+pub struct CollisionMap {
+    pub w: usize,
+    pub h: usize,
+    pub tile_w: usize,
+    pub tile_h: usize,
+    pub shapes: Vec<CollisionShape>,
+}
+
+impl CollisionMap {
+    pub fn new(w: usize, h: usize, tile_w: usize, tile_h: usize, shapes: Vec<CollisionShape>) -> Self {
+        assert_eq!(shapes.len(), w * h, "len(shapes) must be w*h");
+        Self { w, h, tile_w, tile_h, shapes }
+    }
+
+    /// Builds the layer from tile ids (e.g. `TileMap::tiles`) with a lookup.
+    pub fn from_tiles(w: usize, h: usize, tile_w: usize, tile_h: usize, tiles: &[usize], shape_of: impl Fn(usize) -> CollisionShape) -> Self {
+        Self::new(w, h, tile_w, tile_h, tiles.iter().map(|&t| shape_of(t)).collect())
+    }
+
+    pub fn shape_at(&self, c: i32, r: i32) -> CollisionShape {
+        if c < 0 || r < 0 || c as usize >= self.w || r as usize >= self.h { return CollisionShape::Empty; }
+        self.shapes[r as usize * self.w + c as usize]
+    }
+
+    /// Box part of a tile (Full / TopHalf) in world px; slopes have none.
+    fn tile_box(&self, c: i32, r: i32) -> Option<Rect> {
+        let (tw, th) = (self.tile_w as f32, self.tile_h as f32);
+        let (x, y) = (c as f32 * tw, r as f32 * th);
+        match self.shape_at(c, r) {
+            CollisionShape::Full => Some(Rect::new(x, y, tw, th)),
+            CollisionShape::TopHalf => Some(Rect::new(x, y, tw, th / 2.0)),
+            _ => None,
+        }
+    }
+
+    /// Tiles (c, r) that `rect` touches.
+    fn tiles_under(&self, rect: Rect) -> impl Iterator<Item = (i32, i32)> {
+        let (tw, th) = (self.tile_w as f32, self.tile_h as f32);
+        let c0 = (rect.x / tw).floor() as i32;
+        let c1 = ((rect.right() - 0.001) / tw).floor() as i32;
+        let r0 = (rect.y / th).floor() as i32;
+        let r1 = ((rect.bottom() - 0.001) / th).floor() as i32;
+        (r0..=r1).flat_map(move |r| (c0..=c1).map(move |c| (c, r)))
+    }
+
+    /// How far `rect` can actually move by `dy`. Full/TopHalf tiles block both
+    /// ways; tiles where `is_one_way(c, r)` holds only block a fall whose
+    /// previous bottom was at or above their top, so you can jump up through
+    /// them and land on them.
+    pub fn resolve_vertical(&self, rect: Rect, dy: f32, is_one_way: impl Fn(i32, i32) -> bool) -> f32 {
+        let th = self.tile_h as f32;
+        let moved = Rect { y: rect.y + dy, ..rect };
+        let mut allowed = dy;
+        for (c, row) in self.tiles_under(moved) {
+            if let Some(b) = self.tile_box(c, row) {
+                if !aabb_overlap(moved, b) { continue; }
+                if dy > 0.0 { allowed = allowed.min(b.y - rect.bottom()); }
+                if dy < 0.0 { allowed = allowed.max(b.bottom() - rect.y); }
+            } else if dy > 0.0 && is_one_way(c, row) {
+                let top = row as f32 * th;
+                if rect.bottom() <= top && moved.bottom() > top {
+                    allowed = allowed.min(top - rect.bottom());
+                }
+            }
+        }
+        allowed
+    }
+
+    /// Moves `rect` by `delta` and returns it pushed out of solid tiles.
+    /// X is resolved first, then Y; slopes only push up, so walking into one
+    /// climbs it. Keep `delta` under a tile per step (or use `swept_aabb`).
+    pub fn move_rect(&self, rect: Rect, delta: Vec2) -> Rect {
+        let mut r = rect;
+
+        r.x += delta.x;
+        for (c, row) in self.tiles_under(r) {
+            if let Some(b) = self.tile_box(c, row).filter(|b| aabb_overlap(r, *b)) {
+                r.x = if delta.x > 0.0 { b.x - r.w } else if delta.x < 0.0 { b.right() } else { r.x };
+            }
+        }
+
+        r.y += self.resolve_vertical(r, delta.y, |c, row| self.shape_at(c, row) == CollisionShape::OneWay);
+
+        // slopes: keep the bottom-center point on or above the surface
+        let foot_x = r.x + r.w / 2.0;
+        let (tw, th) = (self.tile_w as f32, self.tile_h as f32);
+        let c = (foot_x / tw).floor() as i32;
+        let row = ((r.bottom() - 0.001) / th).floor() as i32;
+        let local = (foot_x - c as f32 * tw) / tw; // 0..1 across the tile
+        let fill = match self.shape_at(c, row) {
+            CollisionShape::SlopeRight => local,
+            CollisionShape::SlopeLeft => 1.0 - local,
+            _ => return r,
+        };
+        let surface = (row + 1) as f32 * th - fill * th;
+        if r.bottom() > surface {
+            r.y = surface - r.h;
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swept_hit_and_miss() {
+        let mover = Rect::new(0.0, 0.0, 2.0, 2.0);
+        let wall = Rect::new(5.0, 0.0, 2.0, 2.0);
+        assert_eq!(swept_aabb(mover, Vec2::new(10.0, 0.0), wall), Some((0.3, Vec2::new(-1.0, 0.0))));
+        // too short, moving away, or passing beside it
+        assert_eq!(swept_aabb(mover, Vec2::new(2.0, 0.0), wall), None);
+        assert_eq!(swept_aabb(mover, Vec2::new(-10.0, 0.0), wall), None);
+        assert_eq!(swept_aabb(mover, Vec2::new(10.0, 0.0), Rect { y: 5.0, ..wall }), None);
+        // sliding along the top edge shares no area
+        assert_eq!(swept_aabb(mover, Vec2::new(10.0, 0.0), Rect { y: 2.0, ..wall }), None);
+        // already overlapping
+        assert_eq!(swept_aabb(mover, Vec2::new(1.0, 1.0), Rect::new(1.0, 1.0, 2.0, 2.0)), Some((0.0, Vec2::ZERO)));
+        // landing from above reports an upward normal
+        let floor = Rect::new(-10.0, 6.0, 20.0, 2.0);
+        assert_eq!(swept_aabb(mover, Vec2::new(0.0, 8.0), floor), Some((0.5, Vec2::new(0.0, -1.0))));
+    }
+
+    #[test]
+    fn swept_corner_graze_is_a_miss() {
+        // halfway through the step the mover's bottom-right corner touches the
+        // box's bottom-left corner at (4, 2) and nothing more: entry == exit
+        let mover = Rect::new(0.0, 0.0, 2.0, 2.0);
+        let corner = Rect::new(4.0, 0.0, 2.0, 2.0);
+        assert_eq!(swept_aabb(mover, Vec2::new(4.0, 4.0), corner), None);
+        // a hair further in, it is a hit on the same corner
+        let hit = swept_aabb(mover, Vec2::new(4.0, 4.0), Rect { y: 0.1, ..corner }).unwrap();
+        assert!(hit.0 > 0.49 && hit.0 < 0.51);
+    }
+
+    /// 4x3 tiles of 8 px: a block at (2, 0) and a floor row with a `/` slope
+    /// at (1, 2) and a `\` slope at (3, 2).
+    fn test_map() -> CollisionMap {
+        use CollisionShape::*;
+        CollisionMap::new(4, 3, 8, 8, vec![
+            Empty, Empty, Full, Empty,
+            Empty, Empty, Empty, Empty,
+            Full, SlopeRight, Full, SlopeLeft,
+        ])
+    }
+
+    #[test]
+    fn move_rect_stops_at_full_blocks() {
+        let m = test_map();
+        let r = Rect::new(8.0, 1.0, 4.0, 4.0);
+        assert_eq!(m.move_rect(r, Vec2::new(6.0, 0.0)), Rect { x: 12.0, ..r });
+        assert_eq!(m.move_rect(Rect { x: 26.0, ..r }, Vec2::new(-3.0, 0.0)), Rect { x: 24.0, ..r });
+        // head bump from below, landing from above
+        assert_eq!(m.move_rect(Rect::new(17.0, 12.0, 4.0, 4.0), Vec2::new(0.0, -6.0)).y, 8.0);
+        assert_eq!(m.move_rect(Rect::new(2.0, 12.0, 2.0, 2.0), Vec2::new(0.0, 4.0)).y, 14.0);
+        // free movement is untouched
+        assert_eq!(m.move_rect(r, Vec2::new(-2.0, 3.0)), Rect::new(6.0, 4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn move_rect_follows_45_degree_slopes() {
+        let m = test_map();
+        // falling onto `/`: the foot (bottom center) rests on the diagonal,
+        // one pixel higher per pixel to the right
+        for (x, bottom) in [(9.0, 22.0), (11.0, 20.0), (13.0, 18.0)] {
+            let r = m.move_rect(Rect::new(x, 17.0, 2.0, 2.0), Vec2::new(0.0, 4.0));
+            assert_eq!(r.bottom(), bottom, "x = {x}");
+        }
+        // `\` mirrors it
+        let r = m.move_rect(Rect::new(25.0, 17.0, 2.0, 2.0), Vec2::new(0.0, 4.0));
+        assert_eq!(r.bottom(), 18.0);
+        // walking right up the slope climbs as much as it advances
+        let r = m.move_rect(Rect::new(11.0, 18.0, 2.0, 2.0), Vec2::new(2.0, 1.0));
+        assert_eq!((r.x, r.bottom()), (13.0, 18.0));
+    }
+
+    #[test]
+    fn one_way_platform_jump_through_and_land() {
+        // one-way platform at row 1 (top edge y = 8), nothing else
+        let m = CollisionMap::new(1, 3, 8, 8, vec![CollisionShape::Empty, CollisionShape::OneWay, CollisionShape::Empty]);
+        let one_way = |c, r| m.shape_at(c, r) == CollisionShape::OneWay;
+        // jumping up from under it passes straight through
+        let below = Rect::new(2.0, 17.0, 4.0, 4.0);
+        assert_eq!(m.resolve_vertical(below, -12.0, one_way), -12.0);
+        assert_eq!(m.move_rect(below, Vec2::new(0.0, -12.0)).y, 5.0);
+        // still rising through it mid-jump doesn't snag either
+        assert_eq!(m.resolve_vertical(Rect::new(2.0, 6.0, 4.0, 4.0), -3.0, one_way), -3.0);
+        // bottom still below the top edge on the way down: keeps falling
+        assert_eq!(m.resolve_vertical(Rect::new(2.0, 6.0, 4.0, 4.0), 2.0, one_way), 2.0);
+        // falling from above lands on the top edge and stays there
+        let above = Rect::new(2.0, 2.0, 4.0, 4.0);
+        assert_eq!(m.resolve_vertical(above, 5.0, one_way), 2.0);
+        let landed = m.move_rect(above, Vec2::new(0.0, 5.0));
+        assert_eq!(landed.bottom(), 8.0);
+        assert_eq!(m.move_rect(landed, Vec2::new(0.0, 1.0)), landed);
+        // without the predicate the tile is just empty
+        assert_eq!(m.resolve_vertical(above, 5.0, |_, _| false), 5.0);
+    }
+}
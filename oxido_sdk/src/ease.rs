@@ -0,0 +1,117 @@
+//! Easing curves over `t` in 0..=1 (each maps 0 → 0 and 1 → 1) and a
+//! `Tween` that drives one value with them.
+
+use core::f32::consts::PI;
+
+pub fn linear(t: f32) -> f32 { t }
+
+pub fn quad_in(t: f32) -> f32 { t * t }
+pub fn quad_out(t: f32) -> f32 { 1.0 - (1.0 - t) * (1.0 - t) }
+pub fn quad_inout(t: f32) -> f32 {
+    if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0 }
+}
+
+pub fn cubic_in(t: f32) -> f32 { t * t * t }
+pub fn cubic_out(t: f32) -> f32 { 1.0 - (1.0 - t).powi(3) }
+pub fn cubic_inout(t: f32) -> f32 {
+    if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+}
+
+pub fn sine_in(t: f32) -> f32 { 1.0 - (t * PI / 2.0).cos() }
+pub fn sine_out(t: f32) -> f32 { (t * PI / 2.0).sin() }
+pub fn sine_inout(t: f32) -> f32 { -((PI * t).cos() - 1.0) / 2.0 }
+
+/// Ball dropping and settling at 1.
+pub fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+pub fn bounce_in(t: f32) -> f32 { 1.0 - bounce_out(1.0 - t) }
+
+/// Overshoots and wobbles into place.
+pub fn elastic_out(t: f32) -> f32 {
+    if t <= 0.0 { return 0.0; }
+    if t >= 1.0 { return 1.0; }
+    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+}
+
+/// Animates `from` → `to` over `dur_ms` along `easing`.
+#[derive(Clone, Copy)]
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub dur_ms: f32,
+    pub easing: fn(f32) -> f32,
+    pub t: f32, // ms elapsed
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, dur_ms: f32, easing: fn(f32) -> f32) -> Self {
+        Self { from, to, dur_ms, easing, t: 0.0 }
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        self.t = (self.t + dt_ms.max(0.0)).min(self.dur_ms);
+    }
+
+    pub fn value(&self) -> f32 {
+        let p = if self.dur_ms > 0.0 { self.t / self.dur_ms } else { 1.0 };
+        self.from + (self.to - self.from) * (self.easing)(p)
+    }
+
+    pub fn done(&self) -> bool { self.t >= self.dur_ms }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Curve = (&'static str, fn(f32) -> f32);
+
+    const CURVES: [Curve; 13] = [
+        ("linear", linear),
+        ("quad_in", quad_in), ("quad_out", quad_out), ("quad_inout", quad_inout),
+        ("cubic_in", cubic_in), ("cubic_out", cubic_out), ("cubic_inout", cubic_inout),
+        ("sine_in", sine_in), ("sine_out", sine_out), ("sine_inout", sine_inout),
+        ("bounce_in", bounce_in), ("bounce_out", bounce_out),
+        ("elastic_out", elastic_out),
+    ];
+
+    #[test]
+    fn curves_hit_their_endpoints() {
+        for (name, f) in CURVES {
+            assert!(f(0.0).abs() < 1e-6, "{name}(0) = {}", f(0.0));
+            assert!((f(1.0) - 1.0).abs() < 1e-6, "{name}(1) = {}", f(1.0));
+        }
+    }
+
+    #[test]
+    fn tween_runs_and_holds() {
+        let mut tw = Tween::new(10.0, 20.0, 100.0, linear);
+        assert_eq!(tw.value(), 10.0);
+        tw.tick(50.0);
+        assert_eq!(tw.value(), 15.0);
+        assert!(!tw.done());
+        tw.tick(500.0);
+        assert_eq!(tw.value(), 20.0);
+        assert!(tw.done());
+        // zero duration jumps straight to `to` and stays there
+        let mut tw = Tween::new(10.0, 20.0, 0.0, quad_in);
+        assert_eq!(tw.value(), 20.0);
+        assert!(tw.done());
+        tw.tick(16.0);
+        assert_eq!(tw.value(), 20.0);
+    }
+}
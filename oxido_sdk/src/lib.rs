@@ -1,3 +1,9 @@
+pub mod collision;
+pub mod ease;
+pub mod math;
+pub mod mml;
+pub use math::{IVec2, Vec2};
+
 pub const DEFAULT_W: usize = 160;
 pub const DEFAULT_H: usize = 144;
 
@@ -18,6 +24,203 @@ pub fn key_bit(k: Key) -> u32 {
     1u32 << (k as u32)
 }
 
+/// Input bits of this frame and the previous one, for edge detection.
+/// Call `update` once at the top of `oxido_update` with the latest bits.
+#[derive(Clone, Copy, Default)]
+pub struct InputState {
+    pub cur: u32,
+    pub prev: u32,
+}
+
+impl InputState {
+    pub const fn new() -> Self { Self { cur: 0, prev: 0 } }
+    pub fn update(&mut self, bits: u32) {
+        self.prev = self.cur;
+        self.cur = bits;
+    }
+    #[inline] pub fn held(&self, k: Key) -> bool { self.cur & key_bit(k) != 0 }
+    /// Down this frame, up the previous one.
+    #[inline] pub fn just_pressed(&self, k: Key) -> bool { self.cur & !self.prev & key_bit(k) != 0 }
+    /// Up this frame, down the previous one.
+    #[inline] pub fn just_released(&self, k: Key) -> bool { !self.cur & self.prev & key_bit(k) != 0 }
+}
+
+// --- Host imports (provided by the OxidoBoy runtime) -----------------------
+#[cfg(target_arch = "wasm32")]
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn oxido_log(ptr: *const u8, len: usize);
+    fn oxido_save(ptr: *const u8, len: usize) -> u32;
+    fn oxido_load(ptr: *mut u8, max_len: usize) -> u32;
+    fn oxido_now_ms() -> f64;
+    fn oxido_frame_count() -> u64;
+    fn oxido_uptime_ms() -> f64;
+    fn oxido_resize(w: u32, h: u32) -> u32;
+    fn oxido_mouse_pos() -> u64;
+    fn oxido_mouse_buttons() -> u32;
+    fn oxido_play_sfx(id: u32) -> u32;
+    fn oxido_beat_phase(bpm: f32) -> f32;
+    fn oxido_flash(color: u32, ms: u32);
+    fn oxido_asset_len(name_ptr: *const u8, name_len: usize) -> u32;
+    fn oxido_asset_read(name_ptr: *const u8, name_len: usize, dst: *mut u8, max_len: usize) -> u32;
+}
+
+/// Print a debug message on the host's stderr (prefixed with `[game]`).
+pub fn log(msg: &str) {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_log(msg.as_ptr(), msg.len()) }
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("[game] {msg}");
+}
+
+/// Persist `data` as this cart's save RAM (max 64 KiB). Returns `true` on success.
+/// The host writes atomically, so a crash never leaves a half-written save.
+pub fn save_slot(data: &[u8]) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_save(data.as_ptr(), data.len()) != 0 }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = data; false }
+}
+
+/// Milliseconds since the runtime started. Monotonic real time (it keeps
+/// running while the game is paused or slow), not the sum of `dt_ms`.
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_now_ms() }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// Global frame index: 0 during `oxido_init` and the first update, then +1
+/// per frame the game is updated in. Frames frozen on a trap don't count.
+/// Handy for blinking (`frame_count() / 30 % 2`) without a counter of your own.
+/// With `fixed_dt_ms` every step of one frame sees the same value.
+pub fn frame_count() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_frame_count() }
+    #[cfg(not(target_arch = "wasm32"))]
+    { 0 }
+}
+
+/// Milliseconds since the runtime started, read once at the start of each
+/// frame: unlike `now_ms` it doesn't move during a frame, so everything
+/// drawn in it agrees. Headless runs (`oxido bench`) report the sum of their `dt_ms`.
+pub fn uptime_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_uptime_ms() }
+    #[cfg(not(target_arch = "wasm32"))]
+    { now_ms() }
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum MouseButton {
+    Left = 0,
+    Right,
+    Middle,
+}
+
+/// Cursor position in framebuffer pixels (scale and letterbox already undone,
+/// clamped to the screen). `(0, 0)` outside the runtime.
+pub fn mouse_pos() -> (i32, i32) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let v = unsafe { oxido_mouse_pos() };
+        (v as u32 as i32, (v >> 32) as u32 as i32)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    { (0, 0) }
+}
+
+/// `true` while `button` is held.
+pub fn mouse_down(button: MouseButton) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_mouse_buttons() & (1 << button as u32) != 0 }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = button; false }
+}
+
+/// Play sound effect `id` (its index in the manifest's `sfx` list) once.
+/// Returns `false` for an unknown id or when audio is off.
+pub fn play_sfx(id: u32) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_play_sfx(id) != 0 }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = id; false }
+}
+
+/// Position within the current beat at `bpm`: 0.0 on the beat, rising to
+/// just under 1.0 right before the next. Derived from the audio output
+/// clock (samples actually played), not from summed `dt_ms`, so animations
+/// stay in sync with the music however long the game runs. Without audio it
+/// follows real time. 0.0 for a `bpm` that isn't positive.
+pub fn beat_phase(bpm: f32) -> f32 {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_beat_phase(bpm) }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if !(bpm > 0.0 && bpm.is_finite()) { return 0.0; }
+        (now_ms() / 1000.0 * bpm as f64 / 60.0).fract() as f32
+    }
+}
+
+/// Blend `color` (e.g. `rgba(255, 255, 255, 255)`) over the whole screen,
+/// fading out over `ms`: hits, lightning, transitions. Purely cosmetic: it
+/// happens after `oxido_draw_ptr`, the game's framebuffer is untouched and
+/// nothing waits for it. A new call restarts the flash; `ms` 0 cancels it.
+/// The color's alpha sets the starting strength.
+pub fn flash(color: u32, ms: u32) {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_flash(color, ms) }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = (color, ms); }
+}
+
+/// Ask the host for a `w`×`h` framebuffer (each side 1..=1024). From the next
+/// draw on, `oxido_draw_ptr/len` must describe a buffer of that size. Returns
+/// `false` if the size was rejected (or outside the runtime).
+pub fn resize(w: u32, h: u32) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_resize(w, h) != 0 }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = (w, h); false }
+}
+
+/// Read the save RAM into `buf`. Returns the number of bytes loaded (0 = no save).
+pub fn load_slot(buf: &mut [u8]) -> usize {
+    #[cfg(target_arch = "wasm32")]
+    unsafe { oxido_load(buf.as_mut_ptr(), buf.len()) as usize }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = buf; 0 }
+}
+
+/// Contents of `name` from the cart's `assets/` folder (path relative to
+/// it, with '/': `"levels/1.txt"`). Empty if there is no such asset (the
+/// host logs the name) or outside the runtime.
+pub fn read_asset(name: &str) -> Vec<u8> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let len = unsafe { oxido_asset_len(name.as_ptr(), name.len()) };
+        if len == u32::MAX { return Vec::new(); }
+        let mut buf = vec![0u8; len as usize];
+        let n = unsafe { oxido_asset_read(name.as_ptr(), name.len(), buf.as_mut_ptr(), buf.len()) };
+        buf.truncate(n as usize);
+        buf
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    { let _ = name; Vec::new() }
+}
+
+/// Packs a rect for the optional `oxido_dirty_rect` export: when a game only
+/// changed part of the screen, the host copies just that area.
+#[inline]
+pub const fn dirty_rect(x: u16, y: u16, w: u16, h: u16) -> u64 {
+    (x as u64) | ((y as u64) << 16) | ((w as u64) << 32) | ((h as u64) << 48)
+}
+
 // Color helpers RGBA packed (little-endian in bytes) 
 #[inline]
 pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
@@ -38,21 +241,230 @@ pub struct Frame<'a> {
 }
 impl<'a> Frame<'a> {
     pub fn clear(&mut self, color: u32) {
-        let bytes = color.to_le_bytes();
-        for px in self.data.chunks_exact_mut(4) {
-            px.copy_from_slice(&bytes);
+        // whole pixels only, like chunks_exact: a trailing partial pixel is left alone
+        let len = self.data.len() / 4 * 4;
+        if len == 0 { return; }
+        let data = &mut self.data[..len];
+        data[..4].copy_from_slice(&color.to_le_bytes());
+        // double the filled prefix each pass: ~log2(pixels) memcpys per clear
+        let mut filled = 4;
+        while filled < len {
+            let n = filled.min(len - filled);
+            data.copy_within(..n, filled);
+            filled += n;
         }
     }
+    /// Fills `w`×`h` from (x, y). A negative extent grows the other way, so
+    /// `rect(x1, y1, x2 - x1, y2 - y1, ..)` works whichever corner is first.
     pub fn rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: u32) {
-        let (W, H) = (self.w as i32, self.h as i32);
+        let (x, w) = if w < 0 { (x.saturating_add(w), w.saturating_neg()) } else { (x, w) };
+        let (y, h) = if h < 0 { (y.saturating_add(h), h.saturating_neg()) } else { (y, h) };
+        let (fw, fh) = (self.w as i32, self.h as i32);
         let bytes = color.to_le_bytes();
-        for yy in y.max(0)..(y + h).min(H) {
-            for xx in x.max(0)..(x + w).min(W) {
+        for yy in y.max(0)..y.saturating_add(h).min(fh) {
+            for xx in x.max(0)..x.saturating_add(w).min(fw) {
                 let idx = ((yy as usize) * self.w + (xx as usize)) * 4;
                 self.data[idx..idx + 4].copy_from_slice(&bytes);
             }
         }
     }
+
+    /// Fills a rect mixing two colors with a 4x4 Bayer pattern: `level` 0 is all
+    /// `color_a`, 16 all `color_b`. The pattern follows screen coordinates, so
+    /// moving shapes don't shimmer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn dither_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color_a: u32, color_b: u32, level: u8) {
+        const BAYER4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        let (fw, fh) = (self.w as i32, self.h as i32);
+        let (a, b) = (color_a.to_le_bytes(), color_b.to_le_bytes());
+        for yy in y.max(0)..y.saturating_add(h).min(fh) {
+            let row = &BAYER4[(yy & 3) as usize];
+            for xx in x.max(0)..x.saturating_add(w).min(fw) {
+                let idx = ((yy as usize) * self.w + (xx as usize)) * 4;
+                let c = if row[(xx & 3) as usize] < level { &b } else { &a };
+                self.data[idx..idx + 4].copy_from_slice(c);
+            }
+        }
+    }
+
+    /// Health/progress bar: `bg` over the whole area, `fg` over the left
+    /// `fraction` (clamped to 0..=1) of it, and a 1 px `border` drawn inside
+    /// the area when given (the fill then spans the inner width).
+    #[allow(clippy::too_many_arguments)]
+    pub fn bar(&mut self, x: i32, y: i32, w: i32, h: i32, fraction: f32, fg: u32, bg: u32, border: Option<u32>) {
+        if w <= 0 || h <= 0 { return; }
+        let (mut ix, mut iy, mut iw, mut ih) = (x, y, w, h);
+        if let Some(b) = border {
+            self.rect(x, y, w, 1, b);
+            self.rect(x, y + h - 1, w, 1, b);
+            self.rect(x, y, 1, h, b);
+            self.rect(x + w - 1, y, 1, h, b);
+            (ix, iy, iw, ih) = (x + 1, y + 1, w - 2, h - 2);
+        }
+        if iw <= 0 || ih <= 0 { return; }
+        // NaN counts as empty
+        let fraction = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+        let filled = (iw as f32 * fraction).round() as i32;
+        self.rect(ix, iy, iw, ih, bg);
+        self.rect(ix, iy, filled, ih, fg);
+    }
+
+    /// Pixelates the area: each `block`×`block` cell (counted from `x,y`) is
+    /// filled with the average of its pixels. Cells cut by the area or the
+    /// frame edge average what's left. Growing `block` over a full-screen
+    /// `mosaic(0, 0, w, h, block)` makes a "digitize" transition.
+    pub fn mosaic(&mut self, x: i32, y: i32, w: i32, h: i32, block: u32) {
+        if block <= 1 { return; }
+        let b = block.min(i32::MAX as u32) as i32;
+        let (x0, y0) = (x.max(0), y.max(0));
+        let (x1, y1) = (x.saturating_add(w).min(self.w as i32), y.saturating_add(h).min(self.h as i32));
+        if x0 >= x1 || y0 >= y1 { return; }
+        // first cell touching the clipped area, on the grid anchored at x,y
+        let (cx0, cy0) = (x + (x0 - x) / b * b, y + (y0 - y) / b * b);
+        let mut cy = cy0;
+        while cy < y1 {
+            let (ry0, ry1) = (cy.max(y0), cy.saturating_add(b).min(y1));
+            let mut cx = cx0;
+            while cx < x1 {
+                let (rx0, rx1) = (cx.max(x0), cx.saturating_add(b).min(x1));
+                let mut sum = [0u32; 4];
+                for yy in ry0..ry1 {
+                    for xx in rx0..rx1 {
+                        let i = (yy as usize * self.w + xx as usize) * 4;
+                        for (s, &v) in sum.iter_mut().zip(&self.data[i..i + 4]) { *s += v as u32; }
+                    }
+                }
+                let n = ((rx1 - rx0) * (ry1 - ry0)) as u32;
+                let avg = sum.map(|s| ((s + n / 2) / n) as u8);
+                for yy in ry0..ry1 {
+                    for xx in rx0..rx1 {
+                        let i = (yy as usize * self.w + xx as usize) * 4;
+                        self.data[i..i + 4].copy_from_slice(&avg);
+                    }
+                }
+                cx = cx.saturating_add(b);
+            }
+            cy = cy.saturating_add(b);
+        }
+    }
+
+    /// Negates RGB over the whole frame (a one-frame hit flash). Alpha is kept.
+    pub fn invert(&mut self) {
+        for px in self.data.chunks_exact_mut(4) {
+            for c in &mut px[..3] { *c = 255 - *c; }
+        }
+    }
+
+    /// Replaces RGB with its luminance (Rec. 601 weights). Alpha is kept.
+    pub fn grayscale(&mut self) {
+        for px in self.data.chunks_exact_mut(4) {
+            let l = (299 * px[0] as u32 + 587 * px[1] as u32 + 114 * px[2] as u32 + 500) / 1000;
+            px[..3].fill(l as u8);
+        }
+    }
+
+    /// Adds `delta` to each RGB channel, saturating at 0 and 255 (negative
+    /// dims, e.g. while hurt or paused). Alpha is kept.
+    pub fn adjust_brightness(&mut self, delta: i32) {
+        for px in self.data.chunks_exact_mut(4) {
+            for c in &mut px[..3] { *c = (*c as i32).saturating_add(delta).clamp(0, 255) as u8; }
+        }
+    }
+
+    /// Flips the whole frame left-right.
+    pub fn mirror_h(&mut self) {
+        if self.w == 0 { return; }
+        for row in self.data.chunks_exact_mut(self.w * 4).take(self.h) {
+            let (mut l, mut r) = (0, self.w - 1);
+            while l < r {
+                for c in 0..4 { row.swap(l * 4 + c, r * 4 + c); }
+                l += 1;
+                r -= 1;
+            }
+        }
+    }
+
+    /// Flips the whole frame upside down.
+    pub fn mirror_v(&mut self) {
+        let stride = self.w * 4;
+        if stride == 0 { return; }
+        let h = self.h.min(self.data.len() / stride);
+        for y in 0..h / 2 {
+            let (top, bottom) = self.data.split_at_mut((h - 1 - y) * stride);
+            top[y * stride..(y + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+        }
+    }
+
+    /// Shifts each row sideways by `amp * sin(2π * y / period + phase)` pixels
+    /// (heat haze, underwater). Animate `phase` over time; the gap left at the
+    /// edge repeats the row's edge pixel. `period` ≤ 0 (or NaN) does nothing.
+    pub fn wave(&mut self, amp: i32, period: f32, phase: f32) {
+        if self.w == 0 || amp == 0 || period.is_nan() || period <= 0.0 { return; }
+        let w = self.w as i32;
+        for (y, row) in self.data.chunks_exact_mut(self.w * 4).take(self.h).enumerate() {
+            let s = (std::f32::consts::TAU * y as f32 / period + phase).sin();
+            let off = (amp as f32 * s).round().clamp(-w as f32, w as f32) as i32;
+            let n = off.unsigned_abs() as usize * 4;
+            if n == 0 { continue; }
+            let len = row.len();
+            if off > 0 {
+                let edge: [u8; 4] = row[..4].try_into().unwrap();
+                row.copy_within(..len - n, n);
+                for px in row[..n].chunks_exact_mut(4) { px.copy_from_slice(&edge); }
+            } else {
+                let edge: [u8; 4] = row[len - 4..].try_into().unwrap();
+                row.copy_within(n.., 0);
+                for px in row[len - n..].chunks_exact_mut(4) { px.copy_from_slice(&edge); }
+            }
+        }
+    }
+
+    /// FNV-1a (64-bit) over the whole `data` slice, alpha bytes included.
+    /// Stable across platforms, so tests can render a scene and compare
+    /// against a known value.
+    pub fn checksum(&self) -> u64 {
+        self.data.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    #[inline]
+    fn pixel(&self, x: usize, y: usize) -> u32 {
+        let i = (y * self.w + x) * 4;
+        u32::from_le_bytes([self.data[i], self.data[i + 1], self.data[i + 2], self.data[i + 3]])
+    }
+
+    /// Scanline flood fill: recolors the 4-connected region that has the same
+    /// color as (x,y). Uses a heap stack, so a full-screen fill is safe in WASM.
+    pub fn flood_fill(&mut self, x: i32, y: i32, new_color: u32) {
+        if x < 0 || y < 0 || x >= self.w as i32 || y >= self.h as i32 { return; }
+        let target = self.pixel(x as usize, y as usize);
+        if target == new_color { return; }
+        let bytes = new_color.to_le_bytes();
+
+        let mut stack = vec![(x as usize, y as usize)];
+        while let Some((sx, sy)) = stack.pop() {
+            if self.pixel(sx, sy) != target { continue; }
+            // widen to the whole run on this row
+            let mut l = sx;
+            while l > 0 && self.pixel(l - 1, sy) == target { l -= 1; }
+            let mut r = sx;
+            while r + 1 < self.w && self.pixel(r + 1, sy) == target { r += 1; }
+
+            let row = sy * self.w * 4;
+            for xx in l..=r {
+                self.data[row + xx * 4..row + xx * 4 + 4].copy_from_slice(&bytes);
+            }
+            // seed one point per run in the rows above and below
+            for ny in [sy.wrapping_sub(1), sy + 1] {
+                if ny >= self.h { continue; }
+                let mut in_run = false;
+                for xx in l..=r {
+                    let hit = self.pixel(xx, ny) == target;
+                    if hit && !in_run { stack.push((xx, ny)); }
+                    in_run = hit;
+                }
+            }
+        }
+    }
 }
 
 // --- Palettes and Sprites -----------------------------------------------
@@ -62,6 +474,23 @@ pub struct Palette(pub [u32; 4]);
 
 impl Palette {
     pub const GB: Palette = Palette([P0, P1, P2, P3]);
+    pub const GRAY: Palette = Palette([
+        rgba(0, 0, 0, 255), rgba(85, 85, 85, 255), rgba(170, 170, 170, 255), rgba(255, 255, 255, 255),
+    ]);
+    // Accessible presets, dark → light like GB: every step is a clear jump in
+    // brightness, so they stay readable with no color vision at all.
+    /// Black, blue, yellow, white.
+    pub const HIGH_CONTRAST: Palette = Palette([
+        rgba(0, 0, 0, 255), rgba(0, 80, 200, 255), rgba(255, 210, 0, 255), rgba(255, 255, 255, 255),
+    ]);
+    /// Blue/orange (Okabe–Ito hues), no red-vs-green distinctions.
+    pub const DEUTERANOPIA: Palette = Palette([
+        rgba(20, 20, 60, 255), rgba(0, 114, 178, 255), rgba(230, 159, 0, 255), rgba(255, 240, 200, 255),
+    ]);
+    /// Crimson/teal, no blue-vs-yellow distinctions.
+    pub const TRITANOPIA: Palette = Palette([
+        rgba(20, 10, 10, 255), rgba(170, 30, 50, 255), rgba(0, 170, 170, 255), rgba(255, 225, 225, 255),
+    ]);
     #[inline] pub fn color(&self, i: u8) -> u32 { self.0[i as usize] }
 }
 
@@ -73,17 +502,40 @@ pub struct SpriteAtlas {
     pub pixels: Vec<u8>, // indexes 0..=3 per pixel
 }
 
+/// Magic at the start of an `.atlas` file written by `oxido pack`.
+pub const ATLAS_MAGIC: [u8; 4] = *b"OXAT";
+
 impl SpriteAtlas {
+    /// Parses an `.atlas` file: `ATLAS_MAGIC`, then w, h, tile_w, tile_h as
+    /// little-endian u16, then w*h palette indexes (one byte each).
+    /// Returns `None` if the data is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 12 || bytes[..4] != ATLAS_MAGIC { return None; }
+        let field = |i: usize| u16::from_le_bytes([bytes[4 + i * 2], bytes[5 + i * 2]]) as usize;
+        let (w, h, tile_w, tile_h) = (field(0), field(1), field(2), field(3));
+        let pixels = bytes.get(12..12 + w * h)?;
+        if tile_w == 0 || tile_h == 0 || !w.is_multiple_of(tile_w) || !h.is_multiple_of(tile_h) { return None; }
+        Some(Self { w, h, tile_w, tile_h, pixels: pixels.iter().map(|i| i & 0b11).collect() })
+    }
+
     /// Creates an atlas from an indexed (0..=3) buffer of size w*h.
     pub fn from_indexed(pixels: Vec<u8>, w: usize, h: usize, tile_w: usize, tile_h: usize) -> Self {
         assert_eq!(pixels.len(), w * h, "pixels must be w*h");
-        assert!(tile_w > 0 && tile_h > 0 && w % tile_w == 0 && h % tile_h == 0, "tiles must divide atlas");
+        assert!(tile_w > 0 && tile_h > 0 && w.is_multiple_of(tile_w) && h.is_multiple_of(tile_h), "tiles must divide atlas");
         Self { w, h, tile_w, tile_h, pixels }
     }
 
+    /// Number of tiles; valid ids are `0..tile_count()`.
+    pub fn tile_count(&self) -> usize {
+        (self.w / self.tile_w) * (self.h / self.tile_h)
+    }
+
     /// Draws tile `tile_id` at (dx,dy). `index 0` is treated as transparent if `transparent_zero` is true.
+    /// An id past the last tile draws nothing.
+    #[allow(clippy::too_many_arguments)]
     pub fn blit(&self, frame: &mut Frame, dx: i32, dy: i32, tile_id: usize, pal: &Palette,
                 flip_x: bool, flip_y: bool, transparent_zero: bool) {
+        if tile_id >= self.tile_count() { return; }
         let tiles_x = self.w / self.tile_w;
         let sx = (tile_id % tiles_x) * self.tile_w;
         let sy = (tile_id / tiles_x) * self.tile_h;
@@ -97,7 +549,7 @@ impl SpriteAtlas {
 
                 let idx = self.pixels[src_y * self.w + src_x];
                 if transparent_zero && idx == 0 { continue; }
-                let color = pal.color((idx & 0b11) as u8);
+                let color = pal.color(idx & 0b11);
 
                 let x = dx + tx as i32;
                 let y = dy + ty as i32;
@@ -107,24 +559,166 @@ impl SpriteAtlas {
             }
         }
     }
+
+    /// Draws tile `tile_id` rotated by `angle_rad` (clockwise on screen) and
+    /// scaled by `scale` about its center, placed at (cx, cy). Each covered
+    /// screen pixel is mapped back into the tile and takes the nearest texel,
+    /// so there are no holes at any angle. At angle 0 and scale 1 with
+    /// `cx = dx as f32 + tile_w as f32 / 2.0` (and likewise `cy`) it matches
+    /// `blit`, odd tile sizes included.
+    ///
+    /// Cost: a few float ops per pixel of the rotated tile's bounding box
+    /// (clipped to the frame), i.e. grows with `scale²`. A 16×16 tile at
+    /// scale 2 touches ~2k pixels; fine for dozens of sprites, but much
+    /// slower per pixel than `blit`. `scale` ≤ 0 or non-finite draws nothing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_affine(&self, frame: &mut Frame, cx: f32, cy: f32, tile_id: usize, pal: &Palette,
+                       angle_rad: f32, scale: f32, transparent_zero: bool) {
+        if tile_id >= self.tile_count() || !(scale > 0.0 && scale.is_finite()) || !angle_rad.is_finite() { return; }
+        let tiles_x = self.w / self.tile_w;
+        let sx = (tile_id % tiles_x) * self.tile_w;
+        let sy = (tile_id / tiles_x) * self.tile_h;
+        let (tw, th) = (self.tile_w as f32, self.tile_h as f32);
+        let (sin, cos) = angle_rad.sin_cos();
+
+        // bounding box of the rotated, scaled tile, clipped to the frame
+        let ex = (cos.abs() * tw + sin.abs() * th) * 0.5 * scale;
+        let ey = (sin.abs() * tw + cos.abs() * th) * 0.5 * scale;
+        let x0 = (cx - ex).floor().max(0.0) as i32;
+        let y0 = (cy - ey).floor().max(0.0) as i32;
+        let x1 = ((cx + ex).ceil() as i32).min(frame.w as i32);
+        let y1 = ((cy + ey).ceil() as i32).min(frame.h as i32);
+
+        // inverse map: screen pixel center -> tile coordinates
+        let inv = 1.0 / scale;
+        for y in y0..y1 {
+            let ry = y as f32 + 0.5 - cy;
+            for x in x0..x1 {
+                let rx = x as f32 + 0.5 - cx;
+                let u = (cos * rx + sin * ry) * inv + tw * 0.5;
+                let v = (cos * ry - sin * rx) * inv + th * 0.5;
+                if !(u >= 0.0 && v >= 0.0 && u < tw && v < th) { continue; }
+                let idx = self.pixels[(sy + v as usize) * self.w + sx + u as usize];
+                if transparent_zero && idx == 0 { continue; }
+                let di = ((y as usize) * frame.w + (x as usize)) * 4;
+                frame.data[di..di + 4].copy_from_slice(&pal.color(idx & 0b11).to_le_bytes());
+            }
+        }
+    }
+
+    /// Like `blit` (no flips), but only pixels inside `clip` = [x0, y0, x1, y1)
+    /// are written.
+    #[allow(clippy::too_many_arguments)]
+    fn blit_clipped(&self, frame: &mut Frame, dx: i32, dy: i32, tile_id: usize, pal: &Palette,
+                    transparent_zero: bool, clip: [i32; 4]) {
+        if tile_id >= self.tile_count() { return; }
+        let tiles_x = self.w / self.tile_w;
+        let sx = (tile_id % tiles_x) * self.tile_w;
+        let sy = (tile_id / tiles_x) * self.tile_h;
+        let x0 = clip[0].max(dx).max(0);
+        let y0 = clip[1].max(dy).max(0);
+        let x1 = clip[2].min(dx + self.tile_w as i32).min(frame.w as i32);
+        let y1 = clip[3].min(dy + self.tile_h as i32).min(frame.h as i32);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let idx = self.pixels[(sy + (y - dy) as usize) * self.w + sx + (x - dx) as usize];
+                if transparent_zero && idx == 0 { continue; }
+                let di = ((y as usize) * frame.w + (x as usize)) * 4;
+                frame.data[di..di + 4].copy_from_slice(&pal.color(idx & 0b11).to_le_bytes());
+            }
+        }
+    }
 }
 
 // --- TileMap (background with tilemap and scrolling) -------------------
+/// Flip flags stored in the high bits of a `TileMap` tile id, same bits as
+/// Tiled's GIDs (31 = horizontal, 30 = vertical). Bit 29 (Tiled's diagonal
+/// flip) is masked off and ignored. Plain ids below 2^29 need no change.
+pub const TILE_FLIP_X: usize = 1 << 31;
+pub const TILE_FLIP_Y: usize = 1 << 30;
+pub const TILE_ID_MASK: usize = (1 << 29) - 1;
+
+#[inline]
+pub const fn pack_tile(id: usize, flip_x: bool, flip_y: bool) -> usize {
+    (id & TILE_ID_MASK) | if flip_x { TILE_FLIP_X } else { 0 } | if flip_y { TILE_FLIP_Y } else { 0 }
+}
+
+/// `(id, flip_x, flip_y)`
+#[inline]
+pub const fn unpack_tile(tile: usize) -> (usize, bool, bool) {
+    (tile & TILE_ID_MASK, tile & TILE_FLIP_X != 0, tile & TILE_FLIP_Y != 0)
+}
+
 pub struct TileMap {
     pub w: usize,        // width in tiles
     pub h: usize,        // high in tiles
     pub tile_w: usize,   // tile width in px
     pub tile_h: usize,   // tile height in px
-    pub tiles: Vec<usize>, // tile ids (index the atlas)
+    pub tiles: Vec<usize>, // tile ids (index the atlas), optionally with flip bits (`pack_tile`)
+    /// Optional palette index per tile, row-major like `tiles`
+    /// (`pal_idx[r * w + c]`, one byte each). Used by `draw_paletted`.
+    pub pal_idx: Option<Vec<u8>>,
 }
 
 impl TileMap {
     pub fn new(w: usize, h: usize, tile_w: usize, tile_h: usize, tiles: Vec<usize>) -> Self {
         assert_eq!(tiles.len(), w * h, "len(tiles) must be w*h");
-        Self { w, h, tile_w, tile_h, tiles }
+        Self { w, h, tile_w, tile_h, tiles, pal_idx: None }
+    }
+
+    #[inline]
+    fn index(&self, c: i32, r: i32) -> Option<usize> {
+        if c < 0 || r < 0 || c as usize >= self.w || r as usize >= self.h { return None; }
+        Some(r as usize * self.w + c as usize)
     }
 
-    /// Draw the map with pixel scroll (scroll_x, scroll_y).
+    /// Tile at column `c`, row `r` (flip bits included), or `None` off the map.
+    pub fn get_tile(&self, c: i32, r: i32) -> Option<usize> {
+        self.index(c, r).map(|i| self.tiles[i])
+    }
+
+    /// Returns `false` (and changes nothing) if (c, r) is off the map.
+    pub fn set_tile(&mut self, c: i32, r: i32, id: usize) -> bool {
+        match self.index(c, r) {
+            Some(i) => { self.tiles[i] = id; true }
+            None => false,
+        }
+    }
+
+    /// Sets every tile in the `w`×`h` block at (c, r); the part off the map is skipped.
+    pub fn fill_rect(&mut self, c: i32, r: i32, w: i32, h: i32, id: usize) {
+        let (c0, r0) = (c.max(0), r.max(0));
+        let (c1, r1) = (c.saturating_add(w).min(self.w as i32), r.saturating_add(h).min(self.h as i32));
+        for rr in r0..r1 {
+            for cc in c0..c1 {
+                self.tiles[rr as usize * self.w + cc as usize] = id;
+            }
+        }
+    }
+
+    /// Attaches per-tile palette indices (same length and layout as `tiles`).
+    pub fn with_palette_indices(mut self, pal_idx: Vec<u8>) -> Self {
+        assert_eq!(pal_idx.len(), self.w * self.h, "len(pal_idx) must be w*h");
+        self.pal_idx = Some(pal_idx);
+        self
+    }
+
+    /// Folds a camera position of any size into map pixels (`0..w * tile_w`,
+    /// `0..h * tile_h`). The map wraps, so drawing at the result looks the
+    /// same as at the original: an endless scroller can keep its camera in
+    /// `i64` and never overflow.
+    pub fn wrap_scroll(&self, x: i64, y: i64) -> (i32, i32) {
+        let (mw, mh) = self.pixel_size();
+        (x.rem_euclid(mw) as i32, y.rem_euclid(mh) as i32)
+    }
+
+    /// Map size in pixels (at least 1, so it can be used as a modulus).
+    fn pixel_size(&self) -> (i64, i64) {
+        (((self.w * self.tile_w) as i64).max(1), ((self.h * self.tile_h) as i64).max(1))
+    }
+
+    /// Draw the map with pixel scroll (scroll_x, scroll_y). Any `i32` works,
+    /// `i32::MIN` and `i32::MAX` included.
     /// If `transparent_zero` is true, atlas index 0 is treated as transparent.
     pub fn draw(
         &self,
@@ -135,21 +729,54 @@ impl TileMap {
         scroll_y: i32,
         transparent_zero: bool,
     ) {
+        self.draw_impl(frame, atlas, pal, &[], scroll_x, scroll_y, transparent_zero);
+    }
+
+    /// Like `draw`, but each tile uses `palettes[pal_idx[tile]]`. Tiles whose
+    /// index is out of range, or maps without `pal_idx`, use `fallback`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_paletted(
+        &self,
+        frame: &mut Frame,
+        atlas: &SpriteAtlas,
+        palettes: &[Palette],
+        fallback: &Palette,
+        scroll_x: i32,
+        scroll_y: i32,
+        transparent_zero: bool,
+    ) {
+        self.draw_impl(frame, atlas, fallback, palettes, scroll_x, scroll_y, transparent_zero);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_impl(
+        &self,
+        frame: &mut Frame,
+        atlas: &SpriteAtlas,
+        pal: &Palette,
+        palettes: &[Palette],
+        scroll_x: i32,
+        scroll_y: i32,
+        transparent_zero: bool,
+    ) {
+        let per_tile = self.pal_idx.as_deref().filter(|_| !palettes.is_empty());
         let tw = self.tile_w as i32;
         let th = self.tile_h as i32;
         let vw = frame.w as i32;
         let vh = frame.h as i32;
 
+        // Wrap into map pixels first (in i64), so the rest only sees small values
+        let (sx, sy) = self.wrap_scroll(scroll_x as i64, scroll_y as i64);
         // Offset in pixels within the first visible tile
-        let off_x = ((scroll_x % tw) + tw) % tw;
-        let off_y = ((scroll_y % th) + th) % th;
-        // Base tile in the map (with wrap)
-        let base_c = (scroll_x.div_euclid(tw)).rem_euclid(self.w as i32);
-        let base_r = (scroll_y.div_euclid(th)).rem_euclid(self.h as i32);
+        let off_x = sx % tw;
+        let off_y = sy % th;
+        // Base tile in the map
+        let base_c = sx / tw;
+        let base_r = sy / th;
 
-        // +2 to cover edges when there's partial offset
-        let cols = vw / tw + 2;
-        let rows = vh / th + 2;
+        // tiles touching [-off, view): exactly ceil((view + off) / tile)
+        let cols = (vw + off_x + tw - 1) / tw;
+        let rows = (vh + off_y + th - 1) / th;
 
         for r in 0..rows {
             let y = r * th - off_y;
@@ -157,8 +784,12 @@ impl TileMap {
             for c in 0..cols {
                 let x = c * tw - off_x;
                 let map_c = (base_c + c).rem_euclid(self.w as i32) as usize;
-                let tile_id = self.tiles[map_r * self.w + map_c];
-                atlas.blit(frame, x, y, tile_id, pal, false, false, transparent_zero);
+                let i = map_r * self.w + map_c;
+                let tile_pal = per_tile
+                    .and_then(|idx| palettes.get(idx[i] as usize))
+                    .unwrap_or(pal);
+                let (tile_id, fx, fy) = unpack_tile(self.tiles[i]);
+                atlas.blit(frame, x, y, tile_id, tile_pal, fx, fy, transparent_zero);
             }
         }
     }
@@ -166,27 +797,187 @@ impl TileMap {
 
 // ====================== Texto 5x7 (HUD) ======================
 impl<'a> Frame<'a> {
-    /// Draw monospaced 5x7 text. Supports: A-Z, 0-9, space, .:-!/?
+    /// Draw monospaced 5x7 text. Supports: A-Z, 0-9, space, .:-!/?, and
+    /// ÁÉÍÓÚÜÑ¿¡ for Spanish (lowercase letters use the uppercase shapes).
     /// `color`: RGBA (usa P1..P3 o pal.color(i)).
     pub fn text5x7(&mut self, x: i32, y: i32, text: &str, color: u32) {
+        self.text(x, y, text, &Font::MONO, color);
+    }
+
+    /// Like `text5x7`, but stacks the glyphs downward (8 px apart) for side
+    /// labels and meter captions. Glyphs stay upright; blanks still take a row.
+    pub fn text5x7_vertical(&mut self, x: i32, y: i32, text: &str, color: u32) {
+        let mut cy = y;
+        for ch in text.chars() {
+            if let Some(rows) = glyph5x7(ch) {
+                self.glyph_rows(x, cy, &rows, color);
+            }
+            cy = cy.saturating_add(8);
+        }
+    }
+
+    /// Draw `text` with `font`: glyph by glyph, each advancing by
+    /// `font.advance(ch)`. Returns the x just past the last glyph.
+    pub fn text(&mut self, x: i32, y: i32, text: &str, font: &Font, color: u32) -> i32 {
         let mut cx = x;
         for ch in text.chars() {
-            self.char5x7(cx, y, ch, color);
-            cx += 6; // 5 px width + 1 px spacing
+            if let Some(rows) = (font.glyph)(ch) {
+                let (left, _) = font.metrics(ch);
+                self.glyph_rows(cx - left, y, &rows, color);
+            }
+            cx += font.advance(ch);
+        }
+        cx
+    }
+
+    fn glyph_rows(&mut self, x: i32, y: i32, rows: &[u8; 7], color: u32) {
+        let bytes = color.to_le_bytes();
+        let (fw, fh) = (self.w as i32, self.h as i32);
+        // columns of the glyph that land inside the frame
+        let dx0 = 0i32.saturating_sub(x).clamp(0, 5);
+        let dx1 = fw.saturating_sub(x).clamp(0, 5);
+        for (dy, row) in rows.iter().enumerate() {
+            let yy = y.saturating_add(dy as i32);
+            if yy < 0 || yy >= fh { continue; }
+            let line = yy as usize * self.w;
+            // 5 bits useful, from MSB to LSB (bit 4 → x, bit 0 → x+4)
+            for dx in dx0..dx1 {
+                if ((row >> (4 - dx)) & 1) != 0 {
+                    let idx = (line + (x + dx) as usize) * 4;
+                    self.data[idx..idx + 4].copy_from_slice(&bytes);
+                }
+            }
+        }
+    }
+}
+
+/// A 7-row bitmap font plus its spacing rule. `glyph` returns 7 rows of 5
+/// bits (bit 4 = leftmost column) or `None` for unsupported chars, so custom
+/// fonts are just another function.
+#[derive(Clone, Copy)]
+pub struct Font {
+    pub glyph: fn(char) -> Option<[u8; 7]>,
+    /// Fixed 6 px advance (what `text5x7` does) instead of per-glyph widths.
+    pub monospace: bool,
+}
+
+impl Font {
+    /// The built-in 5x7 glyphs on a 6 px grid.
+    pub const MONO: Font = Font { glyph: glyph5x7, monospace: true };
+    /// The built-in 5x7 glyphs, each as wide as its ink (`I`, `1`, `.` are narrow).
+    pub const PROPORTIONAL: Font = Font { glyph: glyph5x7, monospace: false };
+
+    /// Width of a blank glyph (space or unsupported char) in proportional mode.
+    const BLANK_W: i32 = 3;
+
+    /// (first inked column, inked width) of `ch`; blanks are (0, `BLANK_W`).
+    fn metrics(&self, ch: char) -> (i32, i32) {
+        if self.monospace { return (0, 5); }
+        let mask = (self.glyph)(ch).map_or(0, |rows| rows.iter().fold(0u8, |m, r| m | (r & 0b11111)));
+        if mask == 0 { return (0, Self::BLANK_W); }
+        let left = 4 - (7 - mask.leading_zeros() as i32);
+        let right = 4 - mask.trailing_zeros() as i32;
+        (left, right - left + 1)
+    }
+
+    /// Pixels the pen moves after `ch`: its width plus 1 px of spacing.
+    pub fn advance(&self, ch: char) -> i32 {
+        self.metrics(ch).1 + 1
+    }
+
+    /// Width of `text` in pixels, without the spacing after the last glyph.
+    pub fn text_width(&self, text: &str) -> i32 {
+        let w: i32 = text.chars().map(|c| self.advance(c)).sum();
+        (w - 1).max(0)
+    }
+}
+
+/// Reveals `text` a few characters at a time, like RPG dialogue. `'\n'`
+/// starts a new line (8 px lower).
+#[derive(Clone)]
+pub struct Typewriter {
+    pub text: String,
+    pub chars_per_sec: f32,
+    pub shown: f32, // characters revealed so far (fractional between ticks)
+}
+
+impl Typewriter {
+    pub fn new(text: impl Into<String>, chars_per_sec: f32) -> Self {
+        Self { text: text.into(), chars_per_sec, shown: 0.0 }
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        let total = self.text.chars().count() as f32;
+        self.shown = (self.shown + self.chars_per_sec * dt_ms.max(0.0) / 1000.0).min(total);
+    }
+
+    /// Number of characters currently visible.
+    pub fn revealed(&self) -> usize { self.shown as usize }
+
+    pub fn skip(&mut self) { self.shown = self.text.chars().count() as f32; }
+
+    pub fn done(&self) -> bool { self.revealed() >= self.text.chars().count() }
+
+    pub fn draw(&self, frame: &mut Frame, x: i32, y: i32, color: u32) {
+        let mut left = self.revealed();
+        for (i, line) in self.text.split('\n').enumerate() {
+            if left == 0 { break; }
+            let n = line.chars().count().min(left);
+            let end = line.char_indices().nth(n).map_or(line.len(), |(b, _)| b);
+            frame.text5x7(x, y + i as i32 * 8, &line[..end], color);
+            left = left.saturating_sub(n + 1); // +1 for the '\n'
         }
     }
+}
+
+/// One line of 5x7 text scrolling right-to-left inside a `width`-px strip,
+/// repeating with a `GAP` between copies (attract screens, news tickers).
+#[derive(Clone)]
+pub struct Marquee {
+    pub text: String,
+    pub speed_px_s: f32,
+    /// How far the text has scrolled, in 0..`period()`.
+    pub offset: f32,
+    pub width: i32,
+}
+
+impl Marquee {
+    /// Blank pixels between the end of the text and its next copy.
+    pub const GAP: i32 = 24;
+
+    pub fn new(text: impl Into<String>, speed_px_s: f32, width: i32) -> Self {
+        Self { text: text.into(), speed_px_s, offset: 0.0, width }
+    }
+
+    /// Distance after which the scroll repeats: text width plus `GAP`.
+    pub fn period(&self) -> i32 {
+        Font::MONO.text_width(&self.text) + Self::GAP
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        let period = self.period() as f32;
+        self.offset = (self.offset + self.speed_px_s * dt_ms / 1000.0).rem_euclid(period);
+    }
 
-    fn char5x7(&mut self, x: i32, y: i32, ch: char, color: u32) {
-        if let Some(rows) = glyph5x7(ch) {
-            for (dy, row) in rows.iter().enumerate() {
-                // 5 bits useful, from MSB to LSB (bit 4 → x, bit 0 → x+4)
-                for dx in 0..5 {
-                    if ((row >> (4 - dx)) & 1) != 0 {
-                        // an individual pixel: use rect 1x1 to avoid touching internals
-                        self.rect(x + dx as i32, y + dy as i32, 1, 1, color);
+    /// Draws the strip from (x, y); nothing lands outside x..x+width.
+    pub fn draw(&self, frame: &mut Frame, x: i32, y: i32, color: u32) {
+        let (x1, period) = (x + self.width, self.period());
+        let mut start = x - self.offset as i32;
+        while start < x1 {
+            for (i, ch) in self.text.chars().enumerate() {
+                let cx = start + i as i32 * 6;
+                if cx + 5 <= x || cx >= x1 { continue; }
+                let Some(rows) = glyph5x7(ch) else { continue };
+                for (dy, row) in rows.iter().enumerate() {
+                    for dx in 0..5 {
+                        let px = cx + dx;
+                        if px >= x && px < x1 && (row >> (4 - dx)) & 1 != 0 {
+                            frame.rect(px, y + dy as i32, 1, 1, color);
+                        }
                     }
                 }
             }
+            start += period;
         }
     }
 }
@@ -244,6 +1035,17 @@ fn glyph5x7(ch: char) -> Option<[u8; 7]> {
         'X' => [0b10001,0b10001,0b01010,0b00100,0b01010,0b10001,0b10001],
         'Y' => [0b10001,0b01010,0b00100,0b00100,0b00100,0b00100,0b00100],
         'Z' => [0b11111,0b00001,0b00010,0b00100,0b01000,0b10000,0b11111],
+
+        // Spanish: the mark takes the top row(s), the letter is one row shorter
+        'Á' | 'á' => [0b00010,0b01110,0b10001,0b11111,0b10001,0b10001,0b10001],
+        'É' | 'é' => [0b00010,0b11111,0b10000,0b11110,0b10000,0b10000,0b11111],
+        'Í' | 'í' => [0b00010,0b01110,0b00100,0b00100,0b00100,0b00100,0b01110],
+        'Ó' | 'ó' => [0b00010,0b01110,0b10001,0b10001,0b10001,0b10001,0b01110],
+        'Ú' | 'ú' => [0b00010,0b10001,0b10001,0b10001,0b10001,0b10001,0b01110],
+        'Ü' | 'ü' => [0b01010,0,0b10001,0b10001,0b10001,0b10001,0b01110],
+        'Ñ' | 'ñ' => [0b01101,0b10010,0b10001,0b11001,0b10101,0b10011,0b10001],
+        '¿' => [0b00100,0,0b00100,0b00100,0b01000,0b10001,0b01110],
+        '¡' => [0,0b00100,0,0b00100,0b00100,0b00100,0b00100],
         _ => return None,
     };
     Some(g)
@@ -307,3 +1109,1016 @@ impl Animator {
         else { self.frames[self.idx] }
     }
 }
+
+// ====================== Draw list (z-sorted sprites) ======================
+/// One queued sprite. Lower `z` is drawn first; equal `z` keeps push order.
+#[derive(Clone, Copy)]
+pub struct DrawCmd {
+    pub z: i32,
+    pub tile: usize,
+    pub x: i32,
+    pub y: i32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub pal: Palette,
+}
+
+/// Collects sprites during a frame and draws them back-to-front on `flush`
+/// (painter's algorithm). The buffer is reused, so after the first frames
+/// it no longer allocates.
+#[derive(Default)]
+pub struct DrawList {
+    cmds: Vec<(DrawCmd, u32)>, // (cmd, push order)
+}
+
+impl DrawList {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn push(&mut self, cmd: DrawCmd) {
+        let seq = self.cmds.len() as u32;
+        self.cmds.push((cmd, seq));
+    }
+
+    pub fn len(&self) -> usize { self.cmds.len() }
+    pub fn is_empty(&self) -> bool { self.cmds.is_empty() }
+
+    /// Draws everything sorted by `z` (index 0 transparent) and empties the list.
+    pub fn flush(&mut self, frame: &mut Frame, atlas: &SpriteAtlas) {
+        self.cmds.sort_unstable_by_key(|(c, seq)| (c.z, *seq));
+        for (c, _) in &self.cmds {
+            atlas.blit(frame, c.x, c.y, c.tile, &c.pal, c.flip_x, c.flip_y, true);
+        }
+        self.cmds.clear();
+    }
+}
+
+// ====================== Sprite (entity) ======================
+/// A moving thing on screen: position and velocity in pixels (velocity per
+/// second), the atlas tile it shows, and a hitbox relative to its top-left
+/// corner (e.g. `Rect::new(4.0, 4.0, 8.0, 8.0)` for the middle of a 16×16
+/// cell). Drive `tile`/flips yourself or from an `Animator` with
+/// `set_anim_frame`; draw directly or through a `DrawList` with `draw_cmd`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sprite {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub tile: usize,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub hitbox: collision::Rect,
+}
+
+impl Sprite {
+    /// At (x, y), standing still, showing `tile` unflipped.
+    pub fn new(x: f32, y: f32, tile: usize, hitbox: collision::Rect) -> Self {
+        Self { x, y, tile, hitbox, ..Self::default() }
+    }
+
+    /// Moves by velocity × `dt_ms` (explicit Euler; no collision).
+    pub fn integrate(&mut self, dt_ms: f32) {
+        let dt = dt_ms / 1000.0;
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+    }
+
+    /// The hitbox in screen/world coordinates, for `aabb_overlap` and friends.
+    pub fn hitbox_world(&self) -> collision::Rect {
+        collision::Rect::new(self.x + self.hitbox.x, self.y + self.hitbox.y, self.hitbox.w, self.hitbox.h)
+    }
+
+    /// Shows an animation frame: takes its tile and flips.
+    pub fn set_anim_frame(&mut self, f: AnimFrame) {
+        (self.tile, self.flip_x, self.flip_y) = (f.tile, f.fx, f.fy);
+    }
+
+    /// Pixel the tile is drawn at: the position floored, so a sprite moving
+    /// across 0 doesn't stall for a pixel.
+    #[inline]
+    pub fn screen_pos(&self) -> (i32, i32) {
+        (self.x.floor() as i32, self.y.floor() as i32)
+    }
+
+    /// Blits the tile at `screen_pos` (index 0 transparent).
+    pub fn draw(&self, frame: &mut Frame, atlas: &SpriteAtlas, pal: &Palette) {
+        let (x, y) = self.screen_pos();
+        atlas.blit(frame, x, y, self.tile, pal, self.flip_x, self.flip_y, true);
+    }
+
+    /// The same draw as a `DrawList` entry at depth `z`.
+    pub fn draw_cmd(&self, z: i32, pal: Palette) -> DrawCmd {
+        let (x, y) = self.screen_pos();
+        DrawCmd { z, tile: self.tile, x, y, flip_x: self.flip_x, flip_y: self.flip_y, pal }
+    }
+}
+
+// ====================== Random numbers ======================
+/// Small deterministic PRNG (xorshift32). The same seed always gives the
+/// same sequence on every platform, so it is safe for level generation.
+#[derive(Clone, Copy)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    /// Any seed works; 0 (a fixed point of xorshift) is remapped.
+    pub const fn new(seed: u32) -> Self {
+        Self { state: if seed == 0 { 0x9E37_79B9 } else { seed } }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Uniform in 0.0..1.0 (24 bits of precision).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in `lo..hi`; returns `lo` when the range is empty.
+    pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+        if hi <= lo { return lo; }
+        let span = (hi as i64 - lo as i64) as u64;
+        (lo as i64 + ((self.next_u32() as u64 * span) >> 32) as i64) as i32
+    }
+
+    /// `true` with probability `p` (0..=1).
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+}
+
+// ====================== Timers ======================
+/// One-shot countdown. A default `Timer` is already finished.
+#[derive(Clone, Copy, Default)]
+pub struct Timer {
+    pub remaining_ms: f32,
+}
+
+impl Timer {
+    pub fn start(&mut self, ms: f32) { self.remaining_ms = ms; }
+    pub fn tick(&mut self, dt_ms: f32) {
+        self.remaining_ms = (self.remaining_ms - dt_ms.max(0.0)).max(0.0);
+    }
+    pub fn finished(&self) -> bool { self.remaining_ms <= 0.0 }
+}
+
+/// "Can act again in `period_ms`": `try_fire` succeeds at most once per
+/// period and restarts the wait by itself.
+#[derive(Clone, Copy, Default)]
+pub struct Cooldown {
+    pub period_ms: f32,
+    pub timer: Timer,
+}
+
+impl Cooldown {
+    /// Starts ready to fire.
+    pub const fn new(period_ms: f32) -> Self { Self { period_ms, timer: Timer { remaining_ms: 0.0 } } }
+    pub fn tick(&mut self, dt_ms: f32) { self.timer.tick(dt_ms); }
+    pub fn ready(&self) -> bool { self.timer.finished() }
+
+    pub fn try_fire(&mut self) -> bool {
+        if !self.ready() { return false; }
+        self.timer.start(self.period_ms);
+        true
+    }
+}
+
+// ====================== Screen shake ======================
+/// Decaying camera jitter. `tick` returns an offset to add to the scroll;
+/// it settles back to (0, 0) once the magnitude drops below half a pixel.
+#[derive(Clone, Copy)]
+pub struct ScreenShake {
+    pub mag: f32,   // current amplitude in px
+    pub decay: f32, // exponential falloff per second (higher = shorter shake)
+    pub t: f32,     // ms since the last trigger
+    rng: Rng,
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self { Self::new(8.0) }
+}
+
+impl ScreenShake {
+    pub const fn new(decay: f32) -> Self {
+        Self { mag: 0.0, decay, t: 0.0, rng: Rng::new(0x2545_F491) }
+    }
+
+    /// Starts a shake; a weaker hit doesn't cut a stronger one short.
+    pub fn trigger(&mut self, mag: f32) {
+        self.mag = self.mag.max(mag);
+        self.t = 0.0;
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) -> (i32, i32) {
+        self.t += dt_ms;
+        self.mag *= (-self.decay * dt_ms.max(0.0) / 1000.0).exp();
+        if self.mag < 0.5 {
+            self.mag = 0.0;
+            return (0, 0);
+        }
+        let jx = self.rng.next_f32() * 2.0 - 1.0;
+        let jy = self.rng.next_f32() * 2.0 - 1.0;
+        ((jx * self.mag).round() as i32, (jy * self.mag).round() as i32)
+    }
+}
+
+// ====================== Fades ======================
+impl<'a> Frame<'a> {
+    /// Blends `color` over the whole frame: `amount` 0 leaves it untouched, 1
+    /// covers it completely. The frame's alpha is kept.
+    pub fn fade_overlay(&mut self, amount: f32, color: u32) {
+        let a = (amount.clamp(0.0, 1.0) * 256.0) as u32;
+        if a == 0 { return; }
+        let c = color.to_le_bytes();
+        for px in self.data.chunks_exact_mut(4) {
+            for i in 0..3 {
+                px[i] = ((px[i] as u32 * (256 - a) + c[i] as u32 * a) >> 8) as u8;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FadeDir {
+    /// From the color to the scene (amount 1 → 0).
+    In,
+    /// From the scene to the color (amount 0 → 1).
+    Out,
+}
+
+/// Timed scene transition. Typical use:
+///
+/// ```ignore
+/// // oxido_update: FADE.tick(dt_ms); if FADE.done() && FADE.dir == FadeDir::Out { load_next_level(); FADE = Fade::new(FadeDir::In, 400.0); }
+/// // oxido_draw_ptr, after drawing the scene:
+/// f.fade_overlay(FADE.amount(), P0);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Fade {
+    pub t: f32, // ms elapsed
+    pub dir: FadeDir,
+    pub dur_ms: f32,
+}
+
+impl Fade {
+    pub const fn new(dir: FadeDir, dur_ms: f32) -> Self { Self { t: 0.0, dir, dur_ms } }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        self.t = (self.t + dt_ms.max(0.0)).min(self.dur_ms);
+    }
+
+    /// Overlay strength for `Frame::fade_overlay` (0..=1).
+    pub fn amount(&self) -> f32 {
+        let p = if self.dur_ms > 0.0 { self.t / self.dur_ms } else { 1.0 };
+        match self.dir {
+            FadeDir::In => 1.0 - p,
+            FadeDir::Out => p,
+        }
+    }
+
+    pub fn done(&self) -> bool { self.t >= self.dur_ms }
+}
+
+// ====================== Particles ======================
+/// What a particle looks like: an atlas tile or a single pixel of color.
+#[derive(Clone, Copy)]
+pub enum ParticleLook {
+    Tile(usize),
+    Color(u32),
+}
+
+#[derive(Clone, Copy)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32, // px/s
+    pub vy: f32,
+    pub life_ms: f32,
+    pub look: ParticleLook,
+}
+
+/// Fixed-capacity particle pool: storage is reserved in `new`, so spawning
+/// never allocates. When full, `spawn` drops the new particle.
+pub struct Particles {
+    items: Vec<Particle>,
+    cap: usize,
+    /// Added to `vy` every second (px/s²); 0 for floating sparks.
+    pub gravity: f32,
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Self {
+        Self { items: Vec::with_capacity(capacity), cap: capacity, gravity: 0.0 }
+    }
+
+    /// Returns `false` if the pool is full.
+    pub fn spawn(&mut self, x: f32, y: f32, vx: f32, vy: f32, life_ms: f32, look: ParticleLook) -> bool {
+        if self.items.len() >= self.cap { return false; }
+        self.items.push(Particle { x, y, vx, vy, life_ms, look });
+        true
+    }
+
+    pub fn tick(&mut self, dt_ms: f32) {
+        let dt = dt_ms / 1000.0;
+        let mut i = 0;
+        while i < self.items.len() {
+            let p = &mut self.items[i];
+            p.life_ms -= dt_ms;
+            if p.life_ms <= 0.0 {
+                self.items.swap_remove(i);
+                continue;
+            }
+            p.vy += self.gravity * dt;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            i += 1;
+        }
+    }
+
+    /// Tile particles are skipped when `atlas` is `None` (index 0 transparent).
+    /// Positions are floored like `Sprite::screen_pos`.
+    pub fn draw(&self, frame: &mut Frame, atlas: Option<&SpriteAtlas>, pal: &Palette) {
+        for p in &self.items {
+            let (x, y) = (p.x.floor() as i32, p.y.floor() as i32);
+            match (p.look, atlas) {
+                (ParticleLook::Color(c), _) => frame.rect(x, y, 1, 1, c),
+                (ParticleLook::Tile(t), Some(a)) => a.blit(frame, x, y, t, pal, false, false, true),
+                (ParticleLook::Tile(_), None) => {}
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize { self.items.len() }
+    pub fn is_empty(&self) -> bool { self.items.is_empty() }
+    pub fn clear(&mut self) { self.items.clear(); }
+}
+
+// ====================== UI ======================
+
+/// Resizable box from nine atlas tiles, row-major: top-left, top, top-right,
+/// left, center, right, bottom-left, bottom, bottom-right. Corners are drawn
+/// once; edges and center repeat to fill.
+pub struct NinePatch<'a> {
+    pub atlas: &'a SpriteAtlas,
+    pub tiles: [usize; 9],
+    pub transparent_zero: bool,
+}
+
+impl<'a> NinePatch<'a> {
+    pub fn new(atlas: &'a SpriteAtlas, tiles: [usize; 9]) -> Self {
+        Self { atlas, tiles, transparent_zero: false }
+    }
+
+    /// Fills (x, y, w, h). Boxes smaller than two corners share the space
+    /// between the corners, each clipped to its half.
+    pub fn draw(&self, frame: &mut Frame, x: i32, y: i32, w: i32, h: i32, pal: &Palette) {
+        if w <= 0 || h <= 0 { return; }
+        // (start, end, first tile position) of the near, middle and far bands
+        let bands = |pos: i32, len: i32, t: i32| {
+            let near = t.min((len + 1) / 2);
+            let far = t.min(len - near);
+            [(pos, pos + near, pos), (pos + near, pos + len - far, pos + near), (pos + len - far, pos + len, pos + len - t)]
+        };
+        let (tw, th) = (self.atlas.tile_w as i32, self.atlas.tile_h as i32);
+        let cols = bands(x, w, tw);
+        let rows = bands(y, h, th);
+
+        for (r, &(y0, y1, ty0)) in rows.iter().enumerate() {
+            for (c, &(x0, x1, tx0)) in cols.iter().enumerate() {
+                let tile = self.tiles[r * 3 + c];
+                for ty in (ty0..y1).step_by(th as usize) {
+                    for tx in (tx0..x1).step_by(tw as usize) {
+                        self.atlas.blit_clipped(frame, tx, ty, tile, pal, self.transparent_zero, [x0, y0, x1, y1]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Vertical list with a wrapping cursor (title screens, pause menus).
+#[derive(Clone, Default)]
+pub struct Menu {
+    pub items: Vec<String>,
+    pub selected: usize,
+}
+
+impl Menu {
+    /// Line height used by `draw`.
+    pub const LINE_H: i32 = 9;
+
+    pub fn new<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        Self { items: items.into_iter().map(Into::into).collect(), selected: 0 }
+    }
+
+    pub fn up(&mut self) {
+        let n = self.items.len();
+        if n > 0 { self.selected = (self.selected + n - 1) % n; }
+    }
+
+    pub fn down(&mut self) {
+        let n = self.items.len();
+        if n > 0 { self.selected = (self.selected + 1) % n; }
+    }
+
+    pub fn confirm(&self) -> usize { self.selected }
+
+    /// Moves on Up/Down presses; returns the chosen index when A or Start
+    /// was just pressed.
+    pub fn handle_input(&mut self, input: &InputState) -> Option<usize> {
+        if input.just_pressed(Key::Up) { self.up(); }
+        if input.just_pressed(Key::Down) { self.down(); }
+        (!self.items.is_empty() && (input.just_pressed(Key::A) || input.just_pressed(Key::Start)))
+            .then(|| self.confirm())
+    }
+
+    /// One item per line from (x, y); the selected one in `highlight` with a
+    /// marker in the left gutter, the rest in `pal.color(2)`.
+    pub fn draw(&self, frame: &mut Frame, x: i32, y: i32, pal: &Palette, highlight: u32) {
+        for (i, item) in self.items.iter().enumerate() {
+            let iy = y + i as i32 * Self::LINE_H;
+            if i == self.selected {
+                frame.rect(x, iy + 2, 3, 3, highlight);
+                frame.text5x7(x + 6, iy, item, highlight);
+            } else {
+                frame.text5x7(x + 6, iy, item, pal.color(2));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_edges() {
+        let mut i = InputState::new();
+        // no change while up
+        i.update(0);
+        assert!(!i.held(Key::A) && !i.just_pressed(Key::A) && !i.just_released(Key::A));
+        // press
+        i.update(key_bit(Key::A));
+        assert!(i.held(Key::A) && i.just_pressed(Key::A) && !i.just_released(Key::A));
+        // hold: no second edge
+        i.update(key_bit(Key::A));
+        assert!(i.held(Key::A) && !i.just_pressed(Key::A) && !i.just_released(Key::A));
+        // release, other keys unaffected
+        i.update(key_bit(Key::B));
+        assert!(!i.held(Key::A) && !i.just_pressed(Key::A) && i.just_released(Key::A));
+        assert!(i.just_pressed(Key::B) && !i.just_released(Key::B));
+        // no change while up again
+        i.update(key_bit(Key::B));
+        assert!(!i.just_released(Key::A) && !i.just_pressed(Key::B));
+    }
+
+    #[test]
+    fn flood_fill_stays_inside_a_box() {
+        let (w, h) = (12, 10);
+        let mut buf = vec![0u8; w * h * 4];
+        let mut f = Frame { data: &mut buf, w, h };
+        f.clear(P0);
+        // 1px wall around (3,2)..(8,7), inside is 4x4
+        f.rect(3, 2, 6, 6, P3);
+        f.rect(4, 3, 4, 4, P0);
+        f.flood_fill(5, 5, P2);
+        for y in 0..h {
+            for x in 0..w {
+                let inside = (4..8).contains(&x) && (3..7).contains(&y);
+                let wall = !inside && (3..9).contains(&x) && (2..8).contains(&y);
+                let want = if inside { P2 } else if wall { P3 } else { P0 };
+                assert_eq!(f.pixel(x, y), want, "({x},{y})");
+            }
+        }
+        // filling the outside reaches every pixel around the box
+        f.flood_fill(0, 0, P1);
+        assert_eq!(f.pixel(11, 9), P1);
+        assert_eq!(f.pixel(5, 5), P2);
+    }
+
+    #[test]
+    fn flood_fill_whole_screen() {
+        let (w, h) = (DEFAULT_W, DEFAULT_H);
+        let mut buf = vec![0u8; w * h * 4];
+        let mut f = Frame { data: &mut buf, w, h };
+        f.clear(P0);
+        f.flood_fill(w as i32 - 1, h as i32 - 1, P3);
+        assert!(buf.chunks_exact(4).all(|p| p == P3.to_le_bytes()));
+    }
+
+    #[test]
+    fn screen_shake_settles_at_zero() {
+        let mut s = ScreenShake::new(8.0);
+        s.trigger(6.0);
+        // a single tick can round to (0, 0) mid-shake; settled means mag hit 0
+        let mut frames = 0;
+        while s.mag > 0.0 {
+            let (x, y) = s.tick(16.0);
+            assert!(x.abs() <= 6 && y.abs() <= 6);
+            frames += 1;
+            assert!(frames < 600, "never settled");
+        }
+        assert!(frames > 1);
+        assert!((0..100).all(|_| s.tick(16.0) == (0, 0)));
+        assert_eq!(s.mag, 0.0);
+    }
+
+    #[test]
+    fn rng_sequence_is_pinned() {
+        // xorshift32 (13, 17, 5); changing these breaks every seeded replay
+        let mut r = Rng::new(1);
+        let seq: Vec<u32> = (0..5).map(|_| r.next_u32()).collect();
+        assert_eq!(seq, [0x0004_2021, 0x0408_0601, 0x9dcc_a8c5, 0x1255_994f, 0x8ef9_17d1]);
+        // seed 0 is remapped, not stuck at 0
+        let mut r = Rng::new(0);
+        let seq: Vec<u32> = (0..5).map(|_| r.next_u32()).collect();
+        assert_eq!(seq, [0x510c_4619, 0xe02e_553e, 0x7bb9_8f3a, 0x0183_a8b5, 0xe633_6d1f]);
+    }
+
+    #[test]
+    fn rng_range_bounds() {
+        let mut r = Rng::new(7);
+        assert_eq!(r.range(5, 5), 5);
+        assert_eq!(r.range(5, -3), 5);
+        for (lo, hi) in [(0, 1), (-3, 4), (10, 13), (i32::MIN, i32::MAX)] {
+            for _ in 0..1000 {
+                let v = r.range(lo, hi);
+                assert!(lo <= v && v < hi, "{v} not in {lo}..{hi}");
+            }
+        }
+        // every value of a small range shows up
+        let mut seen = [false; 7];
+        for _ in 0..1000 { seen[(r.range(-3, 4) + 3) as usize] = true; }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn timer_elapses_and_restarts() {
+        let mut t = Timer::default();
+        assert!(t.finished());
+        t.start(100.0);
+        assert!(!t.finished());
+        t.tick(60.0);
+        t.tick(-50.0); // negative dt never rewinds
+        assert!(!t.finished());
+        t.tick(40.0);
+        assert!(t.finished());
+        t.tick(40.0);
+        assert_eq!(t.remaining_ms, 0.0);
+        t.start(10.0);
+        assert!(!t.finished());
+    }
+
+    #[test]
+    fn cooldown_fires_once_per_period() {
+        let mut c = Cooldown::new(100.0);
+        assert!(c.ready());
+        assert!(c.try_fire());
+        assert!(!c.try_fire());
+        c.tick(99.0);
+        assert!(!c.try_fire());
+        c.tick(1.0);
+        assert!(c.try_fire());
+        // firing restarted the wait
+        c.tick(50.0);
+        assert!(!c.ready());
+    }
+
+    #[test]
+    fn particles_expire_and_respect_capacity() {
+        let mut ps = Particles::new(2);
+        let look = ParticleLook::Color(P3);
+        assert!(ps.spawn(0.0, 0.0, 10.0, 0.0, 100.0, look));
+        assert!(ps.spawn(0.0, 0.0, 0.0, 0.0, 300.0, look));
+        assert!(!ps.spawn(0.0, 0.0, 0.0, 0.0, 300.0, look));
+        assert_eq!(ps.len(), 2);
+
+        ps.tick(50.0);
+        assert_eq!(ps.len(), 2);
+        assert_eq!(ps.items.iter().map(|p| p.x).fold(0.0, f32::max), 0.5);
+        ps.tick(50.0); // the 100 ms one runs out
+        assert_eq!(ps.len(), 1);
+        assert!(ps.spawn(0.0, 0.0, 0.0, 0.0, 10.0, look));
+        assert!(!ps.spawn(0.0, 0.0, 0.0, 0.0, 10.0, look));
+        ps.tick(250.0);
+        assert!(ps.is_empty());
+        assert_eq!(ps.items.capacity(), 2);
+    }
+
+    #[test]
+    fn typewriter_counts_chars_not_bytes() {
+        let mut tw = Typewriter::new("¡HÓLA\nYO", 10.0); // 8 chars, 10 bytes
+        tw.tick(250.0);
+        assert_eq!(tw.revealed(), 2);
+        tw.tick(-100.0);
+        assert_eq!(tw.revealed(), 2);
+
+        let render = |tw: &Typewriter| {
+            let mut buf = vec![0u8; 40 * 20 * 4];
+            tw.draw(&mut Frame { data: &mut buf, w: 40, h: 20 }, 1, 1, P3);
+            buf
+        };
+        let text = |lines: &[&str]| {
+            let mut buf = vec![0u8; 40 * 20 * 4];
+            let mut f = Frame { data: &mut buf, w: 40, h: 20 };
+            for (i, l) in lines.iter().enumerate() { f.text5x7(1, 1 + i as i32 * 8, l, P3); }
+            buf
+        };
+        tw.tick(100.0); // cut right after the multibyte 'Ó'
+        assert_eq!(render(&tw), text(&["¡HÓ"]));
+        tw.tick(400.0); // "¡HÓLA", the '\n', then "Y"
+        assert_eq!(tw.revealed(), 7);
+        assert_eq!(render(&tw), text(&["¡HÓLA", "Y"]));
+        assert!(!tw.done());
+        tw.tick(1000.0);
+        assert_eq!(tw.revealed(), 8);
+        assert!(tw.done());
+
+        let mut tw = Typewriter::new("ÑU\n", 1.0);
+        tw.skip();
+        assert_eq!(tw.revealed(), 3);
+        assert!(tw.done());
+    }
+
+    #[test]
+    fn tilemap_get_set_out_of_range() {
+        let mut m = TileMap::new(3, 2, 8, 8, vec![0; 6]);
+        assert!(m.set_tile(2, 1, 7));
+        assert_eq!(m.get_tile(2, 1), Some(7));
+        assert_eq!(m.tiles[5], 7);
+        for (c, r) in [(-1, 0), (0, -1), (3, 0), (0, 2), (i32::MIN, i32::MAX)] {
+            assert_eq!(m.get_tile(c, r), None);
+            assert!(!m.set_tile(c, r, 9));
+        }
+        assert_eq!(m.tiles, [0, 0, 0, 0, 0, 7]);
+    }
+
+    #[test]
+    fn tilemap_fill_rect_clips() {
+        let mut m = TileMap::new(4, 3, 8, 8, vec![0; 12]);
+        m.fill_rect(-1, 1, 3, 5, 1);
+        assert_eq!(m.tiles, [0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0]);
+        m.fill_rect(3, 0, 0, 3, 2); // empty
+        m.fill_rect(2, 2, -4, 1, 2); // negative size fills nothing
+        assert_eq!(m.tiles, [0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0]);
+        // huge sizes clip instead of overflowing
+        m.fill_rect(3, 2, i32::MAX, i32::MAX, 3);
+        m.fill_rect(i32::MIN, i32::MIN, i32::MAX, i32::MAX, 4); // ends at -1
+        assert_eq!(m.tiles, [0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 3]);
+        m.fill_rect(0, 0, i32::MAX, i32::MAX, 5);
+        assert!(m.tiles.iter().all(|&t| t == 5));
+    }
+
+    #[test]
+    fn bar_fills_half_the_inner_width() {
+        let (w, h) = (14, 4);
+        let mut buf = vec![0u8; w * h * 4];
+        let mut f = Frame { data: &mut buf, w, h };
+        let row = |f: &Frame, y: usize| (0..w).map(|x| f.pixel(x, y)).collect::<Vec<_>>();
+        // no border: 10 px wide, half is 5
+        f.bar(1, 0, 10, 4, 0.5, P3, P1, None);
+        let mut want = vec![0; w];
+        want[1..6].fill(P3);
+        want[6..11].fill(P1);
+        for y in 0..h { assert_eq!(row(&f, y), want, "row {y}"); }
+        // border: 12 px wide, the inner 10 are split 5/5 between the edges
+        f.clear(0);
+        f.bar(1, 0, 12, 4, 0.5, P3, P1, Some(P2));
+        let mut edge = vec![0; w];
+        edge[1..13].fill(P2);
+        let mut inner = vec![0; w];
+        inner[1] = P2;
+        inner[2..7].fill(P3);
+        inner[7..12].fill(P1);
+        inner[12] = P2;
+        assert_eq!(row(&f, 0), edge);
+        assert_eq!(row(&f, 1), inner);
+        assert_eq!(row(&f, 2), inner);
+        assert_eq!(row(&f, 3), edge);
+    }
+
+    #[test]
+    fn menu_cursor_wraps() {
+        let mut m = Menu::new(["START", "OPTIONS", "QUIT"]);
+        m.up();
+        assert_eq!(m.selected, 2);
+        m.down();
+        assert_eq!(m.selected, 0);
+        m.down();
+        m.down();
+        m.down();
+        assert_eq!(m.selected, 0);
+        // empty menu: nothing moves and nothing can be confirmed
+        let mut m = Menu::new(Vec::<String>::new());
+        m.up();
+        m.down();
+        assert_eq!(m.selected, 0);
+        let mut input = InputState::new();
+        input.update(key_bit(Key::A));
+        assert_eq!(m.handle_input(&input), None);
+    }
+
+    #[test]
+    fn marquee_scrolls_and_wraps() {
+        let mut m = Marquee::new("HELLO", 60.0, 40);
+        let period = m.period();
+        assert_eq!(period, Font::MONO.text_width("HELLO") + Marquee::GAP);
+        m.tick(100.0);
+        assert_eq!(m.offset, 6.0);
+        // another 60 px puts it past the period (53 px for "HELLO")
+        m.tick(1000.0);
+        assert_eq!(m.offset, 66.0 - period as f32);
+        assert!((0.0..period as f32).contains(&m.offset));
+        // running backwards wraps the other way
+        let mut m = Marquee::new("HELLO", -60.0, 40);
+        m.tick(100.0);
+        assert_eq!(m.offset, period as f32 - 6.0);
+    }
+
+    /// `tiles_x` × `tiles_y` tiles of `tw`×`th`, every texel set to some
+    /// index 0..=3 (varied, so flips and offsets show up).
+    fn test_atlas(tw: usize, th: usize, tiles_x: usize, tiles_y: usize) -> SpriteAtlas {
+        let (w, h) = (tw * tiles_x, th * tiles_y);
+        let pixels = (0..w * h).map(|i| ((i * 7 + i / w) % 4) as u8).collect();
+        SpriteAtlas::from_indexed(pixels, w, h, tw, th)
+    }
+
+    #[test]
+    fn blit_ignores_tile_ids_past_the_atlas() {
+        let atlas = test_atlas(4, 4, 2, 2);
+        assert_eq!(atlas.tile_count(), 4);
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        let mut f = Frame { data: &mut buf, w: 16, h: 16 };
+        f.clear(P1);
+        let before = f.checksum();
+        for id in [atlas.tile_count(), usize::MAX] {
+            atlas.blit(&mut f, 2, 2, id, &Palette::GB, false, false, false);
+            atlas.blit(&mut f, 2, 2, id, &Palette::GB, true, true, true);
+            atlas.blit_affine(&mut f, 8.0, 8.0, id, &Palette::GB, 0.5, 2.0, false);
+        }
+        assert_eq!(f.checksum(), before);
+        // the last valid id still draws
+        atlas.blit(&mut f, 2, 2, atlas.tile_count() - 1, &Palette::GB, false, false, false);
+        assert_ne!(f.checksum(), before);
+    }
+
+    #[test]
+    fn tilemap_sub_tile_scroll_fills_the_edges() {
+        // 13x11 frame over 4x4 tiles: neither side is a whole number of tiles
+        let atlas = test_atlas(4, 4, 2, 2);
+        let map = TileMap::new(3, 2, 4, 4, vec![0, 1, 2, 3, 1, 0]);
+        let (w, h) = (13, 11);
+        let mut buf = vec![0u8; w * h * 4];
+        let mut f = Frame { data: &mut buf, w, h };
+        let unset = rgba(1, 2, 3, 4);
+        for (sx, sy) in (0..4).flat_map(|x| (0..4).map(move |y| (x, y))) {
+            f.clear(unset);
+            map.draw(&mut f, &atlas, &Palette::GB, sx, sy, false);
+            for y in 0..h {
+                for x in 0..w {
+                    let (mx, my) = ((x + sx as usize) % 12, (y + sy as usize) % 8);
+                    let tile = map.tiles[my / 4 * 3 + mx / 4];
+                    let (ax, ay) = (tile % 2 * 4 + mx % 4, tile / 2 * 4 + my % 4);
+                    let want = Palette::GB.color(atlas.pixels[ay * atlas.w + ax]);
+                    assert_eq!(f.pixel(x, y), want, "scroll ({sx},{sy}) at ({x},{y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rect_negative_extent_and_huge_sizes() {
+        let (w, h) = (10, 8);
+        let draw = |x, y, rw, rh| {
+            let mut buf = vec![0u8; w * h * 4];
+            Frame { data: &mut buf, w, h }.rect(x, y, rw, rh, P3);
+            buf
+        };
+        assert_eq!(draw(7, 2, -4, 3), draw(3, 2, 4, 3));
+        assert_eq!(draw(7, 6, -4, -3), draw(3, 3, 4, 3));
+        assert_eq!(draw(3, 2, 4, 3).chunks_exact(4).filter(|p| *p == P3.to_le_bytes()).count(), 12);
+        // extents that overflow i32 clip to the frame instead of panicking
+        let full = draw(0, 0, w as i32, h as i32);
+        assert_eq!(draw(0, 0, i32::MAX, i32::MAX), full);
+        assert_eq!(draw(5, 5, i32::MAX, i32::MAX), draw(5, 5, 5, 3));
+        assert_eq!(draw(i32::MIN, i32::MIN, i32::MAX, i32::MAX), draw(0, 0, 0, 0));
+        assert_eq!(draw(i32::MAX, i32::MAX, i32::MIN, i32::MIN), full);
+    }
+
+    #[test]
+    fn text_clips_like_single_pixels() {
+        // reference: one 1x1 rect per lit bit, which clips on its own
+        let (w, h) = (11, 9);
+        let text = "AÑ?8";
+        for y in -8..=h as i32 + 1 {
+            for x in -20..=w as i32 + 1 {
+                let mut got = vec![0u8; w * h * 4];
+                Frame { data: &mut got, w, h }.text5x7(x, y, text, P3);
+                let mut want = vec![0u8; w * h * 4];
+                let mut f = Frame { data: &mut want, w, h };
+                for (i, ch) in text.chars().enumerate() {
+                    for (dy, row) in glyph5x7(ch).unwrap().iter().enumerate() {
+                        for dx in 0..5 {
+                            if (row >> (4 - dx)) & 1 != 0 { f.rect(x + i as i32 * 6 + dx, y + dy as i32, 1, 1, P3); }
+                        }
+                    }
+                }
+                assert!(got == want, "text at ({x},{y})");
+                if (x, y) == (0, 0) { assert!(got.iter().any(|&b| b != 0)); }
+            }
+        }
+    }
+
+    #[test]
+    fn clear_fills_whole_pixels_only() {
+        let color = rgba(1, 2, 3, 4);
+        // 7 pixels (odd) plus 3 bytes that don't make a pixel
+        let mut buf = vec![0xAAu8; 7 * 4 + 3];
+        let mut f = Frame { data: &mut buf, w: 7, h: 1 };
+        f.clear(color);
+        let (px, tail) = buf.split_at(7 * 4);
+        assert!(px.chunks_exact(4).all(|p| p == color.to_le_bytes()));
+        assert_eq!(tail, [0xAA; 3]);
+        for len in [0, 3, 4, 5, 33 * 4] {
+            let mut buf = vec![0u8; len];
+            Frame { data: &mut buf, w: len / 4, h: 1 }.clear(color);
+            assert!(buf.chunks_exact(4).all(|p| p == color.to_le_bytes()));
+            assert!(buf[len / 4 * 4..].iter().all(|&b| b == 0));
+        }
+    }
+
+    #[test]
+    fn tilemap_extreme_scroll_matches_wrapped() {
+        let atlas = test_atlas(4, 4, 2, 2);
+        let map = TileMap::new(3, 2, 4, 4, vec![0, 1, 2, 3, 1, 0]);
+        let render = |sx: i32, sy: i32| {
+            let mut buf = vec![0u8; 13 * 11 * 4];
+            map.draw(&mut Frame { data: &mut buf, w: 13, h: 11 }, &atlas, &Palette::GB, sx, sy, false);
+            buf
+        };
+        for (x, y) in [(i32::MIN, i32::MIN), (i32::MAX, i32::MAX), (i32::MIN, i32::MAX), (-1, i32::MAX - 3)] {
+            let (wx, wy) = map.wrap_scroll(x as i64, y as i64);
+            assert!(render(x, y) == render(wx, wy), "scroll ({x},{y})");
+        }
+        // i32::MIN = -2^31 ≡ 4 (mod 12) and ≡ 0 (mod 8)
+        assert_eq!(map.wrap_scroll(i32::MIN as i64, i32::MIN as i64), (4, 0));
+        for (x, y) in [(i64::MIN, i64::MAX), (i64::MAX, i64::MIN), (-1, -1)] {
+            let (wx, wy) = map.wrap_scroll(x, y);
+            assert!((0..12).contains(&wx) && (0..8).contains(&wy), "({x},{y}) -> ({wx},{wy})");
+        }
+        assert_eq!(map.wrap_scroll(-1, -1), (11, 7));
+    }
+
+    #[test]
+    fn vertical_text_stacks_8px_apart() {
+        let (w, h) = (7, 26);
+        let render = |f: &dyn Fn(&mut Frame)| {
+            let mut buf = vec![0u8; w * h * 4];
+            f(&mut Frame { data: &mut buf, w, h });
+            buf
+        };
+        let stacked = render(&|f| f.text5x7_vertical(1, 1, "HI", P3));
+        let by_hand = render(&|f| {
+            f.text5x7(1, 1, "H", P3);
+            f.text5x7(1, 9, "I", P3);
+        });
+        assert!(stacked == by_hand);
+        // a blank still takes its row
+        let gap = render(&|f| f.text5x7_vertical(1, 1, "H I", P3));
+        let by_hand = render(&|f| {
+            f.text5x7(1, 1, "H", P3);
+            f.text5x7(1, 17, "I", P3);
+        });
+        assert!(gap == by_hand);
+    }
+
+    #[test]
+    fn spanish_tilde_and_lowercase() {
+        let render = |s: &str| {
+            let mut buf = vec![0u8; 18 * 7 * 4];
+            let mut f = Frame { data: &mut buf, w: 18, h: 7 };
+            f.text5x7(0, 0, s, P3);
+            (0..7).map(|y| (0..18).map(|x| f.pixel(x, y) == P3).collect::<Vec<_>>()).collect::<Vec<_>>()
+        };
+        let word = render("AÑO");
+        let bits = |row: &[bool]| row.iter().fold(0u8, |b, &on| b << 1 | on as u8);
+        // the Ñ sits in columns 6..11: its tilde takes the top two rows
+        assert_eq!(bits(&word[0][6..11]), 0b01101);
+        assert_eq!(bits(&word[1][6..11]), 0b10010);
+        // A and O keep their plain top rows
+        assert_eq!(bits(&word[0][0..5]), 0b01110);
+        assert_eq!(bits(&word[0][12..17]), 0b01110);
+        assert_eq!(render("año"), word);
+        assert_eq!(render("ñ"), render("Ñ"));
+        assert!(glyph5x7('ñ').is_some() && glyph5x7('ÿ').is_none());
+    }
+
+    #[test]
+    fn sprite_moves_and_carries_its_hitbox() {
+        let mut s = Sprite::new(10.0, 20.0, 3, collision::Rect::new(2.0, 4.0, 12.0, 10.0));
+        s.vx = 50.0;
+        s.vy = -100.0;
+        s.integrate(100.0);
+        assert_eq!((s.x, s.y), (15.0, 10.0));
+        s.integrate(0.0);
+        assert_eq!((s.x, s.y), (15.0, 10.0));
+        assert_eq!(s.hitbox_world(), collision::Rect::new(17.0, 14.0, 12.0, 10.0));
+        // floors, so -0.5 is pixel -1
+        s.x = -0.5;
+        assert_eq!(s.screen_pos(), (-1, 10));
+        assert_eq!(s.hitbox_world().x, 1.5);
+    }
+
+    #[test]
+    fn blit_affine_unrotated_matches_blit() {
+        for (tw, th) in [(4, 4), (3, 5), (1, 1)] {
+            let atlas = test_atlas(tw, th, 2, 1);
+            for (dx, dy) in [(2, 3), (-1, -2), (9, 8)] {
+                let mut a = vec![0u8; 12 * 10 * 4];
+                let mut b = a.clone();
+                let mut fa = Frame { data: &mut a, w: 12, h: 10 };
+                let mut fb = Frame { data: &mut b, w: 12, h: 10 };
+                atlas.blit(&mut fa, dx, dy, 1, &Palette::GB, false, false, true);
+                let (cx, cy) = (dx as f32 + tw as f32 / 2.0, dy as f32 + th as f32 / 2.0);
+                atlas.blit_affine(&mut fb, cx, cy, 1, &Palette::GB, 0.0, 1.0, true);
+                assert_eq!(fa.checksum(), fb.checksum(), "{tw}x{th} tile at ({dx},{dy})");
+            }
+        }
+    }
+
+    #[test]
+    fn mirror_h_twice_is_identity() {
+        for (w, h) in [(5, 3), (4, 2), (1, 1)] {
+            let mut buf: Vec<u8> = (0..w * h * 4).map(|i| (i * 37 % 251) as u8).collect();
+            let orig = buf.clone();
+            let mut f = Frame { data: &mut buf, w, h };
+            f.mirror_h();
+            if w > 1 { assert!(f.data != orig.as_slice()); }
+            assert_eq!(f.pixel(0, 0).to_le_bytes(), orig[(w - 1) * 4..w * 4]);
+            f.mirror_h();
+            assert!(buf == orig, "{w}x{h}");
+        }
+    }
+
+    #[test]
+    fn wave_wider_than_the_frame_repeats_the_edge() {
+        let (w, h) = (6, 4);
+        let mut buf = vec![0u8; w * h * 4];
+        let mut f = Frame { data: &mut buf, w, h };
+        for y in 0..h as i32 {
+            f.rect(0, y, 1, 1, P1);
+            f.rect(1, y, 4, 1, P2);
+            f.rect(5, y, 1, 1, P3);
+        }
+        // rows 1 and 3 get sin = ±1, i.e. a shift far past the width
+        f.wave(1000, 4.0, 0.0);
+        let row = |f: &Frame, y| (0..w).map(|x| f.pixel(x, y)).collect::<Vec<_>>();
+        assert_eq!(row(&f, 1), [P1; 6]);
+        assert_eq!(row(&f, 3), [P3; 6]);
+        f.wave(i32::MIN, 4.0, 0.0);
+        f.wave(i32::MAX, 4.0, 0.0);
+        assert_eq!(row(&f, 1), [P1; 6]);
+    }
+
+    #[test]
+    fn dither_rect_levels_and_clipping() {
+        let (w, h) = (8, 8);
+        let draw = |x, y, rw, rh, level| {
+            let mut buf = vec![0u8; w * h * 4];
+            Frame { data: &mut buf, w, h }.dither_rect(x, y, rw, rh, P0, P3, level);
+            buf
+        };
+        let count = |buf: &[u8], c: u32| buf.chunks_exact(4).filter(|p| *p == c.to_le_bytes()).count();
+        assert_eq!(count(&draw(0, 0, 8, 8, 0), P0), 64);
+        assert_eq!(count(&draw(0, 0, 8, 8, 16), P3), 64);
+        assert_eq!(count(&draw(0, 0, 8, 8, 8), P3), 32);
+        // extents that overflow i32 clip to the frame instead of panicking
+        assert_eq!(draw(5, 5, i32::MAX, i32::MAX, 8), draw(5, 5, 3, 3, 8));
+        assert_eq!(draw(0, 0, i32::MAX, i32::MAX, 8), draw(0, 0, 8, 8, 8));
+        assert_eq!(count(&draw(i32::MIN, i32::MIN, i32::MAX, i32::MAX, 8), 0), 64);
+    }
+
+    #[test]
+    fn particles_draw_at_floored_positions() {
+        let mut ps = Particles::new(2);
+        ps.spawn(-0.5, 1.5, 0.0, 0.0, 100.0, ParticleLook::Color(P3));
+        ps.spawn(2.75, -0.25, 0.0, 0.0, 100.0, ParticleLook::Color(P3));
+        let mut buf = vec![0u8; 4 * 4 * 4];
+        let mut f = Frame { data: &mut buf, w: 4, h: 4 };
+        ps.draw(&mut f, None, &Palette::GB);
+        // (-0.5, 1.5) is column -1 (off screen), not column 0; (2.75, -0.25) is row -1
+        assert!(buf.iter().all(|&b| b == 0));
+        let mut ps = Particles::new(1);
+        ps.spawn(2.75, 1.5, 0.0, 0.0, 100.0, ParticleLook::Color(P3));
+        let mut f = Frame { data: &mut buf, w: 4, h: 4 };
+        ps.draw(&mut f, None, &Palette::GB);
+        assert_eq!(f.pixel(2, 1), P3);
+    }
+}
@@ -0,0 +1,171 @@
+//! Small math helpers: float/int vectors, 16.16 fixed point and the
+//! clamp/lerp/wrap one-liners games keep rewriting. No allocation, no deps.
+
+use core::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// ====================== Vectors ======================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f32, y: f32) -> Self { Self { x, y } }
+    #[inline] pub fn scale(self, k: f32) -> Vec2 { Vec2::new(self.x * k, self.y * k) }
+    #[inline] pub fn dot(self, o: Vec2) -> f32 { self.x * o.x + self.y * o.y }
+    #[inline] pub fn len(self) -> f32 { self.dot(self).sqrt() }
+
+    /// Unit vector in the same direction; `ZERO` stays `ZERO`.
+    pub fn normalize(self) -> Vec2 {
+        let l = self.len();
+        if l > 0.0 { self.scale(1.0 / l) } else { Vec2::ZERO }
+    }
+
+    /// Rounded to whole pixels.
+    pub fn round(self) -> IVec2 { IVec2::new(self.x.round() as i32, self.y.round() as i32) }
+}
+
+// `a + b` / `a - b` (or `.add()` / `.sub()` with the trait in scope)
+impl Add for Vec2 { type Output = Vec2; fn add(self, o: Vec2) -> Vec2 { Vec2::new(self.x + o.x, self.y + o.y) } }
+impl Sub for Vec2 { type Output = Vec2; fn sub(self, o: Vec2) -> Vec2 { Vec2::new(self.x - o.x, self.y - o.y) } }
+impl Mul<f32> for Vec2 { type Output = Vec2; fn mul(self, k: f32) -> Vec2 { self.scale(k) } }
+impl Neg for Vec2 { type Output = Vec2; fn neg(self) -> Vec2 { Vec2::new(-self.x, -self.y) } }
+impl AddAssign for Vec2 { fn add_assign(&mut self, o: Vec2) { *self = *self + o; } }
+impl SubAssign for Vec2 { fn sub_assign(&mut self, o: Vec2) { *self = *self - o; } }
+
+/// Integer vector (pixel and tile coordinates).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IVec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl IVec2 {
+    pub const ZERO: IVec2 = IVec2 { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Self { Self { x, y } }
+    pub fn to_vec2(self) -> Vec2 { Vec2::new(self.x as f32, self.y as f32) }
+}
+
+impl Add for IVec2 { type Output = IVec2; fn add(self, o: IVec2) -> IVec2 { IVec2::new(self.x + o.x, self.y + o.y) } }
+impl Sub for IVec2 { type Output = IVec2; fn sub(self, o: IVec2) -> IVec2 { IVec2::new(self.x - o.x, self.y - o.y) } }
+impl Mul<i32> for IVec2 { type Output = IVec2; fn mul(self, k: i32) -> IVec2 { IVec2::new(self.x * k, self.y * k) } }
+
+// ====================== Fixed point (16.16) ======================
+
+/// 16.16 fixed point: bit-exact on every platform, unlike floats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    pub const ONE: Fixed = Fixed(1 << 16);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+    pub const MAX: Fixed = Fixed(i32::MAX);
+
+    /// Representable integers are -32768..=32767; anything outside saturates
+    /// to `MIN`/`MAX` instead of wrapping.
+    pub const fn from_int(v: i32) -> Self {
+        if v > i16::MAX as i32 { Fixed::MAX } else if v < i16::MIN as i32 { Fixed::MIN } else { Fixed(v << 16) }
+    }
+    pub fn from_f32(v: f32) -> Self { Fixed((v * 65536.0).round() as i32) }
+    pub fn to_f32(self) -> f32 { self.0 as f32 / 65536.0 }
+    /// Integer part (rounds toward -inf, so it matches pixel floors).
+    pub const fn floor(self) -> i32 { self.0 >> 16 }
+    pub const fn mul(self, o: Fixed) -> Fixed { Fixed(((self.0 as i64 * o.0 as i64) >> 16) as i32) }
+    /// `o == 0` returns 0 instead of trapping.
+    pub const fn div(self, o: Fixed) -> Fixed {
+        if o.0 == 0 { return Fixed(0); }
+        Fixed((((self.0 as i64) << 16) / o.0 as i64) as i32)
+    }
+}
+
+impl Add for Fixed { type Output = Fixed; fn add(self, o: Fixed) -> Fixed { Fixed(self.0.wrapping_add(o.0)) } }
+impl Sub for Fixed { type Output = Fixed; fn sub(self, o: Fixed) -> Fixed { Fixed(self.0.wrapping_sub(o.0)) } }
+impl Mul for Fixed { type Output = Fixed; fn mul(self, o: Fixed) -> Fixed { Fixed::mul(self, o) } }
+impl Neg for Fixed { type Output = Fixed; fn neg(self) -> Fixed { Fixed(self.0.wrapping_neg()) } }
+
+// ====================== Scalars ======================
+
+/// Like `i32::clamp`, but never panics: with `lo > hi` it returns `lo`.
+#[inline]
+pub fn clamp(v: i32, lo: i32, hi: i32) -> i32 {
+    v.min(hi).max(lo)
+}
+
+#[inline]
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Integer lerp, rounded to nearest.
+#[inline]
+pub fn lerp_i(a: i32, b: i32, t: f32) -> i32 {
+    lerp(a as f32, b as f32, t).round() as i32
+}
+
+/// `v` wrapped into `0..n` (also for negatives); `n <= 0` returns 0.
+#[inline]
+pub fn wrap(v: i32, n: i32) -> i32 {
+    if n <= 0 { 0 } else { v.rem_euclid(n) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec2_ops() {
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.len(), 5.0);
+        assert_eq!(v.normalize(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::ZERO.normalize(), Vec2::ZERO);
+        assert_eq!(v + Vec2::new(1.0, -1.0) - Vec2::new(0.5, 0.5), Vec2::new(3.5, 2.5));
+        assert_eq!(-v * 2.0, Vec2::new(-6.0, -8.0));
+        assert_eq!(Vec2::new(1.5, -1.5).round(), IVec2::new(2, -2));
+        assert_eq!(IVec2::new(2, 3) * 2 - IVec2::new(1, 1), IVec2::new(3, 5));
+    }
+
+    #[test]
+    fn fixed_arithmetic() {
+        let a = Fixed::from_f32(2.5);
+        let b = Fixed::from_int(-2);
+        assert_eq!(a.to_f32(), 2.5);
+        assert_eq!((a * b).to_f32(), -5.0);
+        assert_eq!(a.div(b).to_f32(), -1.25);
+        assert_eq!(a.div(Fixed(0)), Fixed(0));
+        assert_eq!((a + b).to_f32(), 0.5);
+        assert_eq!((b - a).to_f32(), -4.5);
+        // floor rounds toward -inf
+        assert_eq!(Fixed::from_f32(-0.25).floor(), -1);
+        assert_eq!(Fixed::from_f32(1.75).floor(), 1);
+    }
+
+    #[test]
+    fn fixed_edges_do_not_panic() {
+        assert_eq!(Fixed::from_int(32767).floor(), 32767);
+        assert_eq!(Fixed::from_int(-32768).floor(), -32768);
+        assert_eq!(Fixed::from_int(32768), Fixed::MAX);
+        assert_eq!(Fixed::from_int(i32::MIN), Fixed::MIN);
+        // negation and +/- wrap like the rest of the type
+        assert_eq!(-Fixed::MIN, Fixed::MIN);
+        assert_eq!(-Fixed::ONE, Fixed::from_int(-1));
+        assert_eq!(Fixed::MAX + Fixed(1), Fixed::MIN);
+        assert_eq!(Fixed::MIN - Fixed(1), Fixed::MAX);
+    }
+
+    #[test]
+    fn scalar_helpers() {
+        assert_eq!(clamp(5, 0, 3), 3);
+        assert_eq!(clamp(-5, 0, 3), 0);
+        assert_eq!(clamp(1, 4, 2), 4);
+        assert_eq!(lerp(2.0, 4.0, 0.5), 3.0);
+        assert_eq!(lerp_i(0, 10, 0.26), 3);
+        assert_eq!(wrap(-1, 4), 3);
+        assert_eq!(wrap(9, 4), 1);
+        assert_eq!(wrap(5, 0), 0);
+    }
+}
@@ -0,0 +1,166 @@
+//! Music Macro Language: write a melody as text (`"t140 o4 l8 cdeg >c4"`)
+//! and get note/rest events with durations already in milliseconds.
+//!
+//! Supported: notes `c d e f g a b` with `+`/`#` (sharp) or `-` (flat), an
+//! optional length (`4` = quarter, `8` = eighth...) and dots (`c4.`); rests
+//! `r`; `o<n>` octave (0..=8), `<`/`>` one octave down/up; `l<n>` default
+//! length; `t<n>` tempo in BPM (quarter notes). Spaces and `|` are ignored.
+//! Defaults: `t120 o4 l4`.
+
+use core::fmt;
+
+/// One step of a melody.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeqEvent {
+    /// MIDI note number (60 = middle C, `o4c`).
+    Note { midi: u8, dur_ms: f32 },
+    Rest { dur_ms: f32 },
+}
+
+impl SeqEvent {
+    pub fn dur_ms(&self) -> f32 {
+        match *self {
+            SeqEvent::Note { dur_ms, .. } | SeqEvent::Rest { dur_ms } => dur_ms,
+        }
+    }
+}
+
+/// Frequency of a MIDI note (A4 = 69 = 440 Hz), e.g. for a channel's `base_freq`.
+pub fn midi_to_hz(midi: u8) -> f32 {
+    440.0 * 2f32.powf((midi as f32 - 69.0) / 12.0)
+}
+
+/// What went wrong and at which byte offset of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MmlError {
+    pub pos: usize,
+    pub msg: &'static str,
+}
+
+impl fmt::Display for MmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MML error at {}: {}", self.pos, self.msg)
+    }
+}
+
+impl std::error::Error for MmlError {}
+
+pub fn parse(src: &str) -> Result<Vec<SeqEvent>, MmlError> {
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    let (mut tempo, mut octave, mut default_len) = (120u32, 4i32, 4u32);
+    let mut events = Vec::new();
+
+    // digits at `*i`, if any; `Err` if they don't fit in a u32
+    let number = |i: &mut usize| -> Result<Option<u32>, ()> {
+        let start = *i;
+        while *i < bytes.len() && bytes[*i].is_ascii_digit() { *i += 1; }
+        if start == *i { return Ok(None); }
+        src[start..*i].parse().map(Some).map_err(|_| ())
+    };
+    let err = |pos, msg| Err(MmlError { pos, msg });
+
+    while i < bytes.len() {
+        let pos = i;
+        let c = bytes[i].to_ascii_lowercase();
+        i += 1;
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' | b'|' => {}
+            b'<' => octave -= 1,
+            b'>' => octave += 1,
+            b'o' => match number(&mut i) {
+                Ok(Some(n)) if n <= 8 => octave = n as i32,
+                Ok(Some(_)) | Err(()) => return err(pos, "octave must be 0..=8"),
+                Ok(None) => return err(pos, "`o` needs a number"),
+            },
+            b't' => match number(&mut i) {
+                Ok(Some(n)) if n > 0 => tempo = n,
+                Err(()) => return err(pos, "tempo out of range"),
+                Ok(_) => return err(pos, "`t` needs a tempo above 0"),
+            },
+            b'l' => match number(&mut i) {
+                Ok(Some(n)) if n > 0 => default_len = n,
+                Err(()) => return err(pos, "length out of range"),
+                Ok(_) => return err(pos, "`l` needs a length above 0"),
+            },
+            b'a'..=b'g' | b'r' => {
+                let mut semi: i32 = match c {
+                    b'c' => 0, b'd' => 2, b'e' => 4, b'f' => 5, b'g' => 7, b'a' => 9, b'b' => 11,
+                    _ => 0,
+                };
+                if c != b'r' {
+                    while i < bytes.len() && matches!(bytes[i], b'+' | b'#' | b'-') {
+                        semi += if bytes[i] == b'-' { -1 } else { 1 };
+                        i += 1;
+                    }
+                }
+                let len = match number(&mut i) {
+                    Ok(Some(0)) => return err(pos, "length must be above 0"),
+                    Ok(Some(n)) => n,
+                    Ok(None) => default_len,
+                    Err(()) => return err(pos, "length out of range"),
+                };
+                // whole note = 4 beats; each dot adds half of the previous part
+                let mut dur_ms = 240_000.0 / (tempo as f32 * len as f32);
+                let mut part = dur_ms;
+                while i < bytes.len() && bytes[i] == b'.' {
+                    part /= 2.0;
+                    dur_ms += part;
+                    i += 1;
+                }
+                if c == b'r' {
+                    events.push(SeqEvent::Rest { dur_ms });
+                } else {
+                    let midi = 12 * (octave + 1) + semi;
+                    if !(0..=127).contains(&midi) { return err(pos, "note outside the MIDI range"); }
+                    events.push(SeqEvent::Note { midi: midi as u8, dur_ms });
+                }
+            }
+            _ => return err(pos, "unexpected character"),
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_notes_at_the_defaults() {
+        let notes: Vec<_> = [60, 62, 64, 65, 67].iter().map(|&midi| SeqEvent::Note { midi, dur_ms: 500.0 }).collect();
+        assert_eq!(parse("t120 o4 cdefg").unwrap(), notes);
+        assert_eq!(parse("cdefg").unwrap(), notes);
+        assert_eq!(
+            parse("T60 l8 C#4. r | >b-16 <<a").unwrap(),
+            [
+                SeqEvent::Note { midi: 61, dur_ms: 1500.0 },
+                SeqEvent::Rest { dur_ms: 500.0 },
+                SeqEvent::Note { midi: 82, dur_ms: 250.0 },
+                SeqEvent::Note { midi: 57, dur_ms: 500.0 },
+            ]
+        );
+        assert_eq!(parse(" | ").unwrap(), []);
+        assert_eq!(midi_to_hz(69), 440.0);
+    }
+
+    #[test]
+    fn errors_point_at_the_command() {
+        let at = |src| parse(src).unwrap_err().pos;
+        assert_eq!(at("c o9 d"), 2);
+        assert_eq!(at("cd t0"), 3);
+        assert_eq!(at("t120 c0"), 5);
+        assert_eq!(at("l0"), 0);
+        assert_eq!(at("o"), 0);
+        assert_eq!(at("cx"), 1);
+        // `<`/`>` can leave the MIDI range; the note is what fails
+        assert_eq!(at("o0 <<c"), 5);
+        assert_eq!(at("o8 >>c"), 5);
+        // numbers too big for a u32 are errors, not the default
+        assert_eq!(parse("c c99999999999").unwrap_err(), MmlError { pos: 2, msg: "length out of range" });
+        assert_eq!(parse("l99999999999").unwrap_err(), MmlError { pos: 0, msg: "length out of range" });
+        assert_eq!(parse("r t99999999999").unwrap_err(), MmlError { pos: 2, msg: "tempo out of range" });
+        assert_eq!(parse("o99999999999").unwrap_err(), MmlError { pos: 0, msg: "octave must be 0..=8" });
+        assert_eq!(parse("o9").unwrap_err().to_string(), "MML error at 0: octave must be 0..=8");
+    }
+}